@@ -1,15 +1,20 @@
+#![allow(clippy::result_large_err)]
+
 mod statement;
 
 pub mod prelude {
-    pub use crate::{Compile, CompileError, CompileResult, Compiler};
+    pub use crate::{Compile, CompileError, CompileResult, CompileWarning, Compiler};
     pub use tapt_parser::prelude::*;
     pub use tapt_vm::*;
 }
 
-use std::fmt;
+use std::{collections::BTreeMap, error::Error, fmt, mem};
 
-use tapt_parser::prelude::{Expression, FunctionType, Positioned, Span, Statement, Type};
-use tapt_vm::{Chunk, OpCode, Value};
+use tapt_parser::prelude::{
+    Block, Diagnostic, Expression, FunctionType, Operator, Positioned, Precedence, Span, Statement,
+    Type,
+};
+use tapt_vm::{Capture, Chunk, OpCode, Value};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum CompileError {
@@ -50,6 +55,81 @@ pub enum CompileError {
         instance_at: Option<Span>,
         at: Span,
     },
+    OutsideOfLoop {
+        statement: String,
+        at: Span,
+    },
+    ReturnOutsideOfFunction {
+        at: Span,
+    },
+    /// Function with declared output type can finish without returning a value.
+    MissingReturn {
+        expected: Type,
+        at: Span,
+    },
+    NotIterable {
+        found: Type,
+        at: Span,
+    },
+    UnknownType {
+        name: String,
+        at: Span,
+    },
+    /// Type declaration contains the declared type itself, so its values would never end.
+    RecursiveType {
+        name: String,
+        /// Other types the declaration contains itself through, in order.
+        through: Vec<String>,
+        at: Span,
+    },
+    MethodNotCalled {
+        name: String,
+        at: Span,
+    },
+    NonExhaustiveMatch {
+        missing: Vec<String>,
+        at: Span,
+    },
+    RefutablePattern {
+        at: Span,
+    },
+    InconsistentBinding {
+        name: String,
+        at: Span,
+    },
+    /// Value of type `from` can't be converted to `to` with `as`.
+    InvalidCast {
+        from: Type,
+        to: Type,
+        at: Span,
+    },
+    /// Assignment is used as the assigned value, like `a = b = 1`.
+    ChainedAssignment {
+        at: Span,
+    },
+    /// Variant with fields is used without `new`, like `Shape.Circle(1.0)`.
+    VariantWithoutNew {
+        variant: String,
+        /// How the variant is created, `new Shape.Circle(...)`.
+        constructor: String,
+        at: Span,
+    },
+    /// Type of declared variable can't be inferred from its value, like `[]`.
+    TypeAnnotationRequired {
+        at: Span,
+    },
+    /// Function or type of the script has the same name as another declaration or variable of it.
+    AlreadyDeclared {
+        name: String,
+        declared_at: Span,
+        at: Span,
+    },
+}
+
+/// Problem found during compilation which doesn't prevent code from running.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CompileWarning {
+    UnreachablePattern { at: Span },
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -67,9 +147,232 @@ impl fmt::Display for InstanceArgsType {
     }
 }
 
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::VariableNotExist { name, .. } => write!(f, "variable `{name}` doesn't exist"),
+            Self::PropertyNotExist {
+                target, property, ..
+            } => write!(f, "`{target}` has no property `{property}`"),
+            Self::ImmutableVariable { name, .. } => {
+                write!(f, "cannot assign twice to immutable variable `{name}`")
+            }
+            Self::TypeExpected {
+                expected, found, ..
+            } => write!(f, "expected `{expected}`, found `{found}`"),
+            Self::OneOfTypeExpected {
+                expected, found, ..
+            } => {
+                let expected = expected
+                    .iter()
+                    .map(|ty| format!("`{ty}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                write!(f, "expected one of {expected}, found `{found}`")
+            }
+            Self::InvalidArgumentsCount { expected, got, .. } => write!(
+                f,
+                "expected {expected} argument{}, found {got}",
+                if *expected == 1 { "" } else { "s" }
+            ),
+            Self::InvalidInstanceArgs { expected, got, .. } => {
+                write!(f, "expected {expected} fields, found {got} fields")
+            }
+            Self::OutsideOfLoop { statement, .. } => write!(f, "`{statement}` outside of a loop"),
+            Self::ReturnOutsideOfFunction { .. } => f.write_str("`return` outside of a function"),
+            Self::MissingReturn { expected, .. } => {
+                write!(f, "function can finish without returning `{expected}`")
+            }
+            Self::NotIterable { found, .. } => write!(f, "`{found}` is not iterable"),
+            Self::UnknownType { name, .. } => write!(f, "unknown type `{name}`"),
+            Self::RecursiveType { name, .. } => write!(f, "recursive type `{name}`"),
+            Self::MethodNotCalled { name, .. } => write!(f, "method `{name}` must be called"),
+            Self::NonExhaustiveMatch { missing, .. } => {
+                write!(
+                    f,
+                    "non-exhaustive match, `{}` not covered",
+                    missing.join("`, `")
+                )
+            }
+            Self::RefutablePattern { .. } => f.write_str("refutable pattern in binding"),
+            Self::InconsistentBinding { name, .. } => {
+                write!(f, "variable `{name}` is not bound in all alternatives")
+            }
+            Self::InvalidCast { from, to, .. } => write!(f, "cannot cast `{from}` as `{to}`"),
+            Self::ChainedAssignment { .. } => f.write_str("assignment has no value to assign"),
+            Self::VariantWithoutNew { variant, .. } => {
+                write!(f, "variant `{variant}` can only be created with `new`")
+            }
+            Self::TypeAnnotationRequired { .. } => f.write_str("type annotation required"),
+            Self::AlreadyDeclared { name, .. } => write!(f, "`{name}` is declared more than once"),
+        }
+    }
+}
+
+impl Error for CompileError {}
+
+impl CompileError {
+    /// Span of the code which caused the error.
+    #[must_use]
+    pub const fn span(&self) -> Span {
+        match self {
+            Self::VariableNotExist { accessed_at, .. }
+            | Self::ImmutableVariable { accessed_at, .. } => *accessed_at,
+            Self::PropertyNotExist { at, .. }
+            | Self::TypeExpected { at, .. }
+            | Self::OneOfTypeExpected { at, .. }
+            | Self::InvalidArgumentsCount { at, .. }
+            | Self::InvalidInstanceArgs { at, .. }
+            | Self::OutsideOfLoop { at, .. }
+            | Self::ReturnOutsideOfFunction { at }
+            | Self::MissingReturn { at, .. }
+            | Self::NotIterable { at, .. }
+            | Self::UnknownType { at, .. }
+            | Self::RecursiveType { at, .. }
+            | Self::MethodNotCalled { at, .. }
+            | Self::NonExhaustiveMatch { at, .. }
+            | Self::RefutablePattern { at }
+            | Self::ChainedAssignment { at }
+            | Self::TypeAnnotationRequired { at }
+            | Self::VariantWithoutNew { at, .. }
+            | Self::InconsistentBinding { at, .. }
+            | Self::InvalidCast { at, .. }
+            | Self::AlreadyDeclared { at, .. } => *at,
+        }
+    }
+
+    /// Describes the error with labels pointing into the compiled source.
+    #[must_use]
+    #[allow(clippy::too_many_lines)]
+    pub fn diagnostic(&self) -> Diagnostic {
+        let diagnostic = Diagnostic::new(self.to_string());
+        let span = self.span();
+
+        match self {
+            Self::VariableNotExist { .. } => {
+                diagnostic.with_primary(span, "not found in this scope")
+            }
+            Self::PropertyNotExist {
+                target, defined_at, ..
+            } => {
+                let diagnostic = diagnostic.with_primary(span, "unknown property");
+
+                match defined_at {
+                    Some(defined_at) => {
+                        diagnostic.with_secondary(*defined_at, format!("`{target}` defined here"))
+                    }
+                    None => diagnostic,
+                }
+            }
+            Self::ImmutableVariable { declared_at, .. } => {
+                let diagnostic = diagnostic.with_primary(span, "cannot assign twice");
+
+                match declared_at {
+                    Some(declared_at) => diagnostic
+                        .with_secondary(*declared_at, "first assignment")
+                        .with_note("declare the variable with `let` to make it mutable"),
+                    None => diagnostic,
+                }
+            }
+            Self::TypeExpected { expected, .. } => {
+                diagnostic.with_primary(span, format!("expected `{expected}`"))
+            }
+            Self::OneOfTypeExpected { found, .. } | Self::NotIterable { found, .. } => {
+                diagnostic.with_primary(span, format!("this is `{found}`"))
+            }
+            Self::InvalidArgumentsCount {
+                got, function_at, ..
+            } => {
+                let diagnostic = diagnostic.with_primary(span, format!("called with {got}"));
+
+                match function_at {
+                    Some(function_at) => {
+                        diagnostic.with_secondary(*function_at, "function defined here")
+                    }
+                    None => diagnostic,
+                }
+            }
+            Self::InvalidInstanceArgs {
+                expected,
+                instance_at,
+                ..
+            } => {
+                let diagnostic =
+                    diagnostic.with_primary(span, format!("expected {expected} fields"));
+
+                match instance_at {
+                    Some(instance_at) => diagnostic.with_secondary(*instance_at, "defined here"),
+                    None => diagnostic,
+                }
+            }
+            Self::OutsideOfLoop { statement, .. } => {
+                diagnostic.with_primary(span, format!("cannot `{statement}` outside of a loop"))
+            }
+            Self::ReturnOutsideOfFunction { .. } => {
+                diagnostic.with_primary(span, "cannot `return` outside of a function")
+            }
+            Self::MissingReturn { expected, .. } => {
+                diagnostic.with_primary(span, format!("expected `{expected}` to be returned"))
+            }
+            Self::UnknownType { .. } => diagnostic.with_primary(span, "not found in this scope"),
+            Self::RecursiveType { name, through, .. } => diagnostic
+                .with_primary(
+                    span,
+                    if through.is_empty() {
+                        format!("`{name}` contains itself")
+                    } else {
+                        format!(
+                            "`{name}` contains itself through `{}`",
+                            through.join("`, `")
+                        )
+                    },
+                )
+                .with_note("every value of the type would have to contain another one"),
+            Self::MethodNotCalled { name, .. } => {
+                diagnostic.with_primary(span, format!("use `{name}(...)` to call it"))
+            }
+            Self::NonExhaustiveMatch { .. } => {
+                diagnostic.with_primary(span, "patterns don't cover every value")
+            }
+            Self::RefutablePattern { .. } => {
+                diagnostic.with_primary(span, "pattern may not match the value")
+            }
+            Self::InconsistentBinding { name, .. } => {
+                diagnostic.with_primary(span, format!("pattern doesn't bind `{name}`"))
+            }
+            Self::InvalidCast { from, .. } => {
+                diagnostic.with_primary(span, format!("this is `{from}`"))
+            }
+            Self::ChainedAssignment { .. } => diagnostic
+                .with_primary(span, "this is an assignment")
+                .with_note("assign each variable in a separate statement"),
+            Self::VariantWithoutNew { constructor, .. } => {
+                diagnostic.with_primary(span, format!("use `{constructor}`"))
+            }
+            Self::TypeAnnotationRequired { .. } => diagnostic
+                .with_primary(span, "can't infer type of this value")
+                .with_note("declare type of the variable, like `let values: [int] = []`"),
+            Self::AlreadyDeclared { declared_at, .. } => diagnostic
+                .with_primary(span, "declared again here")
+                .with_secondary(*declared_at, "first declared here")
+                .with_note("functions and types are declared before the script runs"),
+        }
+    }
+}
+
 pub type CompileResult<T> = std::result::Result<T, CompileError>;
 
-#[derive(Debug)]
+/// Checks whether unresolved type `ty` mentions type `name`.
+fn refers_to(ty: &Type, name: &str) -> bool {
+    match ty {
+        Type::Named(value) => value == name,
+        Type::Optional(value) | Type::Array(value) => refers_to(value, name),
+        _ => false,
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Variable {
     pub name: String,
     pub depth: usize,
@@ -78,31 +381,156 @@ pub struct Variable {
     pub span: Option<Span>,
 }
 
-#[derive(Default)]
+/// Where value of variable is stored.
+#[derive(Clone, Copy)]
+enum Access {
+    Global(usize),
+    /// Slot of the frame with given offset relative to the current one.
+    Local(isize, usize),
+    Upvalue(usize),
+}
+
+#[derive(Debug, Clone)]
+pub struct Loop {
+    /// Scope depth outside of the loop body.
+    pub depth: usize,
+    /// Position of the first instruction of the loop, `continue` jumps here.
+    pub start: usize,
+    /// Positions of `break` jumps which must be patched to the end of the loop.
+    pub breaks: Vec<usize>,
+}
+
+#[derive(Default, Clone)]
 pub struct Compiler {
     pub variables: Vec<Variable>,
     pub scope_depth: usize,
+    pub loops: Vec<Loop>,
+    /// User-defined types which can be referenced in type annotations.
+    pub types: BTreeMap<String, Type>,
+    pub warnings: Vec<CompileWarning>,
+    /// Compiler of the function this one is nested in.
+    pub enclosing: Option<Box<Self>>,
+    /// Variables captured from enclosing functions, index is the upvalue slot.
+    pub upvalues: Vec<(String, Capture)>,
+    /// Output type of the function being compiled, `None` at the top level.
+    pub output: Option<Type>,
 }
 
 pub trait GetType {
+    /// # Errors
+    ///
+    /// Returns compile error if type can't be resolved
     fn get_type(&self, compiler: &Compiler, span: Span) -> CompileResult<Type>;
 }
 
 pub trait Compile<O = ()> {
+    /// # Errors
+    ///
+    /// Returns compile error
     fn compile(self, compiler: &mut Compiler, span: Span, chunk: &mut Chunk) -> CompileResult<O>;
 }
 
 pub trait CompileAssign<O = ()> {
+    /// # Errors
+    ///
+    /// Returns compile error
     fn compile(
         self,
         compiler: &mut Compiler,
         span: Span,
         chunk: &mut Chunk,
-        assign_value: Option<Positioned<Expression>>,
+        assignment: Option<Assignment>,
     ) -> CompileResult<O>;
 }
 
+/// Value assigned to the place by `=` or by compound assignment like `+=`.
+pub struct Assignment {
+    /// Operator combining current value of the place with the assigned one, if assignment is
+    /// compound.
+    pub operator: Option<Positioned<Operator>>,
+    pub value: Positioned<Expression>,
+}
+
+impl Assignment {
+    /// Checks that assigned value can be stored into the place of type `ty`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CompileError::TypeExpected` if types are not compatible, or
+    /// `CompileError::ChainedAssignment` if assigned value is an assignment itself
+    pub fn check(&self, compiler: &Compiler, span: Span, ty: &Type) -> CompileResult<()> {
+        if let Expression::Binary(value) = &self.value.value
+            && value.operator.value.precedence() == Precedence::PAssign
+        {
+            return Err(CompileError::ChainedAssignment {
+                at: self.value.span,
+            });
+        }
+
+        let found = self.value.get_type(compiler, span)?;
+
+        if let Some(operator) = &self.operator {
+            // strings can only be appended to
+            let string = operator.value == Operator::Add && ty == &Type::String;
+
+            if !string && !matches!(ty, Type::Float | Type::Integer) {
+                return Err(CompileError::OneOfTypeExpected {
+                    expected: vec![Type::Float, Type::Integer],
+                    found: ty.clone(),
+                    at: operator.span,
+                });
+            }
+
+            if &found != ty {
+                return Err(CompileError::TypeExpected {
+                    expected: ty.clone(),
+                    found,
+                    at: self.value.span,
+                });
+            }
+        } else if !ty.compare(&found) {
+            return Err(CompileError::TypeExpected {
+                expected: ty.clone(),
+                found,
+                at: self.value.span,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Compiles assigned value. Compound assignment expects current value of the place on the
+    /// stack and combines it with the assigned one.
+    ///
+    /// # Errors
+    ///
+    /// Returns compile error if value failed to compile
+    pub fn compile(self, compiler: &mut Compiler, chunk: &mut Chunk) -> CompileResult<()> {
+        let (span, value) = self.value.unpack();
+
+        value.compile(compiler, span, chunk, None)?;
+
+        if let Some(operator) = self.operator {
+            chunk.push(
+                operator.span.line,
+                match operator.value {
+                    Operator::Add => OpCode::Add,
+                    Operator::Sub => OpCode::Sub,
+                    Operator::Mul => OpCode::Mul,
+                    Operator::Div => OpCode::Div,
+                    _ => unreachable!(),
+                },
+            );
+        }
+
+        Ok(())
+    }
+}
+
 pub trait CompilePositioned<O> {
+    /// # Errors
+    ///
+    /// Returns compile error
     fn compile(self, compiler: &mut Compiler, chunk: &mut Chunk) -> CompileResult<O>;
 }
 
@@ -124,6 +552,12 @@ impl Compiler {
         Self {
             variables: Vec::new(),
             scope_depth: 0,
+            loops: Vec::new(),
+            types: BTreeMap::new(),
+            warnings: Vec::new(),
+            enclosing: None,
+            upvalues: Vec::new(),
+            output: None,
         }
     }
 
@@ -146,6 +580,9 @@ impl Compiler {
         )
     }
 
+    /// Compiles top-level script. If it fails, compiler is left as it was before the call, so
+    /// it can compile the next script.
+    ///
     /// # Errors
     ///
     /// Returns compile error
@@ -153,13 +590,49 @@ impl Compiler {
         &mut self,
         block: Vec<Positioned<Statement>>,
         return_statement: Option<Positioned<Statement>>,
+    ) -> CompileResult<Chunk> {
+        let variables = self.variables.clone();
+        let types = self.types.clone();
+        let scope_depth = self.scope_depth;
+        let loops = self.loops.len();
+
+        let result = self.compile_script(block, return_statement);
+
+        if result.is_err() {
+            self.variables = variables;
+            self.types = types;
+            self.scope_depth = scope_depth;
+            self.loops.truncate(loops);
+        }
+
+        result
+    }
+
+    fn compile_script(
+        &mut self,
+        block: Vec<Positioned<Statement>>,
+        return_statement: Option<Positioned<Statement>>,
     ) -> CompileResult<Chunk> {
         let mut chunk = Chunk::new();
+        // declarations don't depend on the code before them, so they are moved to the start of
+        // the chunk and can be used by the code above them
+        let mut declarations = Vec::new();
+
+        statement::hoist(self, &block)?;
 
         for value in block {
+            let start = chunk.len();
+            let hoisted = statement::is_declaration(&value.value);
+
             value.compile(self, &mut chunk)?;
+
+            if hoisted {
+                declarations.extend(chunk.code.drain(start..));
+            }
         }
 
+        chunk.code.splice(0..0, declarations);
+
         if let Some(statement) = return_statement {
             let line = statement.span.line;
 
@@ -173,22 +646,153 @@ impl Compiler {
         Ok(chunk)
     }
 
-    fn push_scope(&mut self) {
+    const fn push_scope(&mut self) {
         self.scope_depth += 1;
     }
 
     fn pop_scope(&mut self, _: usize, _: &mut Chunk) {
-        // for _ in self
-        //     .variables
-        //     .iter()
-        //     .filter(|value| value.depth == self.scope_depth)
-        // {
-        //     chunk.push(line, OpCode::Pop);
-        // }
+        // variables of the scope live inside its frame, which is gone now
+        while self
+            .variables
+            .last()
+            .is_some_and(|variable| variable.depth == self.scope_depth)
+        {
+            self.variables.pop();
+        }
 
         self.scope_depth -= 1;
     }
 
+    fn push_loop(&mut self, start: usize) {
+        self.loops.push(Loop {
+            depth: self.scope_depth,
+            start,
+            breaks: Vec::new(),
+        });
+    }
+
+    fn pop_loop(&mut self, chunk: &mut Chunk) {
+        let Some(value) = self.loops.pop() else {
+            unreachable!()
+        };
+
+        let end = chunk.len() - 1;
+
+        for jump in value.breaks {
+            Self::patch_jump(chunk, jump, end);
+        }
+    }
+
+    fn compile_break(&mut self, span: Span, chunk: &mut Chunk) -> CompileResult<()> {
+        let scope_depth = self.scope_depth;
+
+        let Some(value) = self.loops.last_mut() else {
+            return Err(CompileError::OutsideOfLoop {
+                statement: "break".into(),
+                at: span,
+            });
+        };
+
+        for _ in value.depth..scope_depth {
+            chunk.push(span.line, OpCode::PopFrame);
+        }
+
+        value.breaks.push(chunk.len());
+
+        chunk.push(span.line, OpCode::Jump(0));
+
+        Ok(())
+    }
+
+    fn compile_continue(&self, span: Span, chunk: &mut Chunk) -> CompileResult<()> {
+        let Some(value) = self.loops.last() else {
+            return Err(CompileError::OutsideOfLoop {
+                statement: "continue".into(),
+                at: span,
+            });
+        };
+
+        for _ in value.depth..self.scope_depth {
+            chunk.push(span.line, OpCode::PopFrame);
+        }
+
+        Self::jump_back(chunk, span.line, value.start);
+
+        Ok(())
+    }
+
+    fn compile_return(
+        &mut self,
+        value: Option<Positioned<Expression>>,
+        span: Span,
+        chunk: &mut Chunk,
+    ) -> CompileResult<()> {
+        let Some(output) = self.output.clone() else {
+            return Err(CompileError::ReturnOutsideOfFunction { at: span });
+        };
+
+        let found = match &value {
+            Some(value) => value.get_type(self, span)?,
+            None => Type::None,
+        };
+
+        if !output.compare(&found) {
+            return Err(CompileError::TypeExpected {
+                expected: output,
+                found,
+                at: value.map_or(span, |value| value.span),
+            });
+        }
+
+        if let Some(value) = value {
+            let (span, value) = value.unpack();
+
+            value.compile(self, span, chunk, None)?;
+        }
+
+        // expressions without value may leave nothing on the stack
+        if found == Type::None {
+            Self::compile_const(chunk, span.line, Value::None);
+        }
+
+        // the outermost scope of function compiler lives in the frame of the call
+        chunk.push(span.line, OpCode::Exit(self.scope_depth - 1));
+
+        Ok(())
+    }
+
+    /// Compiles loop body inside of its own frame, value of the trailing expression is discarded.
+    ///
+    /// `prelude` is compiled right after the frame is pushed, so it can declare loop variables.
+    fn compile_loop_body(
+        &mut self,
+        chunk: &mut Chunk,
+        body: Positioned<Block>,
+        prelude: impl FnOnce(&mut Self, &mut Chunk) -> CompileResult<()>,
+    ) -> CompileResult<()> {
+        let (span, body) = body.unpack();
+
+        chunk.push(span.line, OpCode::PushFrame);
+
+        self.push_scope();
+
+        prelude(self, chunk)?;
+
+        for value in body.statements {
+            value.compile(self, chunk)?;
+        }
+
+        if let Some(statement) = body.return_statement {
+            statement.compile(self, chunk)?;
+        }
+
+        self.pop_scope(span.line, chunk);
+
+        chunk.push(span.line, OpCode::PopFrame);
+
+        Ok(())
+    }
+
     fn track_position(
         &mut self,
         chunk: &mut Chunk,
@@ -201,25 +805,282 @@ impl Compiler {
         Ok((start, chunk.len()))
     }
 
-    pub fn get_var(
+    /// Finds variable visible in the current function, including ones of enclosing functions.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CompileError::VariableNotExist` if there is no variable with such name
+    pub fn get_var(&self, accessed_at: Span, name: impl AsRef<str>) -> CompileResult<&Variable> {
+        let name = name.as_ref();
+
+        if let Some(slot) = self.local_slot(name) {
+            return Ok(&self.variables[slot]);
+        }
+
+        self.enclosing.as_ref().map_or_else(
+            || {
+                Err(CompileError::VariableNotExist {
+                    name: name.to_string(),
+                    accessed_at,
+                })
+            },
+            |enclosing| enclosing.get_var(accessed_at, name),
+        )
+    }
+
+    /// Finds variable which is about to be assigned.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CompileError::VariableNotExist` if there is no variable with such name, or
+    /// `CompileError::ImmutableVariable` if it isn't mutable
+    pub fn get_mutable_var(
         &self,
         accessed_at: Span,
         name: impl AsRef<str>,
-    ) -> CompileResult<(usize, &Variable)> {
+    ) -> CompileResult<&Variable> {
         let name = name.as_ref();
+        let variable = self.get_var(accessed_at, name)?;
+
+        if variable.mutable {
+            Ok(variable)
+        } else {
+            Err(CompileError::ImmutableVariable {
+                name: name.to_string(),
+                accessed_at,
+                declared_at: variable.span,
+            })
+        }
+    }
 
-        for (index, var) in self.variables.iter().enumerate().rev() {
-            if var.name == name {
-                return Ok((index, var));
+    fn local_slot(&self, name: &str) -> Option<usize> {
+        self.variables.iter().rposition(|var| var.name == name)
+    }
+
+    /// Checks whether variable in `slot` is declared at the top level of the script.
+    fn is_global(&self, slot: usize) -> bool {
+        self.enclosing.is_none() && self.variables[slot].depth == 0
+    }
+
+    /// Returns slot of global variable `name`, if it isn't shadowed by local one.
+    fn global_slot(&self, name: &str) -> Option<usize> {
+        self.enclosing.as_ref().map_or_else(
+            || self.local_slot(name).filter(|slot| self.is_global(*slot)),
+            |enclosing| enclosing.global_slot(name),
+        )
+    }
+
+    /// Returns index of upvalue holding variable `name` of enclosing function, capturing it if
+    /// it isn't captured yet.
+    ///
+    /// Globals are never captured, functions access them directly.
+    fn resolve_upvalue(&mut self, name: &str) -> Option<usize> {
+        if let Some(index) = self
+            .upvalues
+            .iter()
+            .position(|(upvalue, _)| upvalue == name)
+        {
+            return Some(index);
+        }
+
+        let enclosing = self.enclosing.as_mut()?;
+
+        let capture = if let Some(slot) = enclosing.local_slot(name) {
+            if enclosing.is_global(slot) {
+                return None;
             }
+
+            Capture::Local(
+                enclosing.frame_offset(enclosing.variables[slot].depth),
+                slot,
+            )
+        } else {
+            Capture::Upvalue(enclosing.resolve_upvalue(name)?)
+        };
+
+        self.upvalues.push((name.to_string(), capture));
+
+        Some(self.upvalues.len() - 1)
+    }
+
+    fn slot_access(&self, slot: usize) -> Access {
+        if self.is_global(slot) {
+            Access::Global(slot)
+        } else {
+            Access::Local(self.frame_offset(self.variables[slot].depth), slot)
+        }
+    }
+
+    /// Resolves where value of variable `name` is stored.
+    fn resolve_var(&mut self, accessed_at: Span, name: &str) -> CompileResult<Access> {
+        if let Some(slot) = self.local_slot(name) {
+            return Ok(self.slot_access(slot));
         }
 
-        Err(CompileError::VariableNotExist {
-            name: name.to_string(),
-            accessed_at,
+        if let Some(index) = self.resolve_upvalue(name) {
+            return Ok(Access::Upvalue(index));
+        }
+
+        self.global_slot(name)
+            .map(Access::Global)
+            .ok_or_else(|| CompileError::VariableNotExist {
+                name: name.to_string(),
+                accessed_at,
+            })
+    }
+
+    fn load(chunk: &mut Chunk, line: usize, access: Access) {
+        chunk.push(
+            line,
+            match access {
+                Access::Global(slot) => OpCode::GetGlobal(slot),
+                Access::Local(frame, slot) => OpCode::GetLocal(frame, slot),
+                Access::Upvalue(index) => OpCode::GetUpvalue(index),
+            },
+        );
+    }
+
+    fn store(chunk: &mut Chunk, line: usize, access: Access) {
+        chunk.push(
+            line,
+            match access {
+                Access::Global(slot) => OpCode::SetGlobal(slot),
+                Access::Local(frame, slot) => OpCode::SetLocal(frame, slot),
+                Access::Upvalue(index) => OpCode::SetUpvalue(index),
+            },
+        );
+    }
+
+    /// # Errors
+    ///
+    /// Returns `CompileError::VariableNotExist` if there is no variable with such name
+    pub fn load_var(
+        &mut self,
+        chunk: &mut Chunk,
+        accessed_at: Span,
+        name: &str,
+    ) -> CompileResult<()> {
+        let access = self.resolve_var(accessed_at, name)?;
+
+        Self::load(chunk, accessed_at.line, access);
+
+        Ok(())
+    }
+
+    /// Stores value on top of the stack into variable `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CompileError::VariableNotExist` if there is no variable with such name
+    pub fn store_var(
+        &mut self,
+        chunk: &mut Chunk,
+        accessed_at: Span,
+        name: &str,
+    ) -> CompileResult<()> {
+        let access = self.resolve_var(accessed_at, name)?;
+
+        Self::store(chunk, accessed_at.line, access);
+
+        Ok(())
+    }
+
+    /// Loads variable of the current function by its slot.
+    fn load_slot(&self, chunk: &mut Chunk, line: usize, slot: usize) {
+        Self::load(chunk, line, self.slot_access(slot));
+    }
+
+    /// Stores value on top of the stack into variable of the current function by its slot.
+    fn store_slot(&self, chunk: &mut Chunk, line: usize, slot: usize) {
+        Self::store(chunk, line, self.slot_access(slot));
+    }
+
+    /// Runs `func` with compiler of a function nested in this one, so it can capture variables.
+    ///
+    /// Returns output of `func` and variables captured by the function.
+    fn nested<T>(
+        &mut self,
+        func: impl FnOnce(&mut Self) -> CompileResult<T>,
+    ) -> CompileResult<(T, Vec<Capture>)> {
+        let mut compiler = Self::new();
+
+        compiler.types.clone_from(&self.types);
+        compiler.enclosing = Some(Box::new(mem::take(self)));
+
+        let output = func(&mut compiler);
+
+        // enclosing compiler is restored even if the function failed to compile
+        *self = *compiler.enclosing.take().unwrap_or_else(|| unreachable!());
+
+        self.warnings.append(&mut compiler.warnings);
+
+        Ok((
+            output?,
+            compiler
+                .upvalues
+                .into_iter()
+                .map(|(_, capture)| capture)
+                .collect(),
+        ))
+    }
+
+    /// Replaces names of user-defined types in `ty` with their definitions.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CompileError::UnknownType` if type with such name is not declared
+    pub fn resolve_type(&self, ty: Type, at: Span) -> CompileResult<Type> {
+        Ok(match ty {
+            Type::Named(name) => match self.types.get(&name) {
+                Some(ty) => ty.clone(),
+                None => return Err(CompileError::UnknownType { name, at }),
+            },
+            Type::Optional(value) => Type::Optional(Box::new(self.resolve_type(*value, at)?)),
+            Type::Array(value) => Type::Array(Box::new(self.resolve_type(*value, at)?)),
+            Type::Function(FunctionType { args, output_type }) => Type::Function(FunctionType {
+                args: args
+                    .into_iter()
+                    .map(|ty| self.resolve_type(ty, at))
+                    .collect::<CompileResult<_>>()?,
+                output_type: Box::new(self.resolve_type(*output_type, at)?),
+            }),
+            ty => ty,
         })
     }
 
+    /// Resolves type of a field of type `owner` being declared.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CompileError::RecursiveType` if field type refers to `owner`, or
+    /// `CompileError::UnknownType` if it refers to type which is not declared
+    pub fn resolve_field_type(&self, owner: &str, ty: Type, at: Span) -> CompileResult<Type> {
+        if refers_to(&ty, owner) {
+            return Err(CompileError::RecursiveType {
+                name: owner.to_string(),
+                through: Vec::new(),
+                at,
+            });
+        }
+
+        self.resolve_type(ty, at)
+    }
+
+    /// Returns copy of the compiler with a new scope containing `bindings`.
+    ///
+    /// Used to resolve types of expressions which see variables introduced by patterns.
+    fn scoped(&self, bindings: impl IntoIterator<Item = (String, Type)>) -> Self {
+        let mut compiler = self.clone();
+
+        compiler.push_scope();
+
+        for (name, ty) in bindings {
+            compiler.create_var(name, false, ty, None);
+        }
+
+        compiler
+    }
+
     fn get_or_create_var(
         &mut self,
         name: String,
@@ -227,14 +1088,9 @@ impl Compiler {
         mutable: bool,
         span: Option<Span>,
     ) -> usize {
+        // redeclaration in the same scope reuses slot, variables of outer scopes are shadowed
         for (index, var) in self.variables.iter_mut().enumerate().rev() {
-            // it's already created by type checker (lmao)
             if var.depth == self.scope_depth && var.name == name {
-                return index;
-            }
-
-            // shadowing :jokerge:
-            if var.name == name {
                 var.mutable = mutable;
                 var.ty = ty;
                 var.span = span;
@@ -258,15 +1114,28 @@ impl Compiler {
         self.variables.len() - 1
     }
 
+    /// Returns offset of the frame where variable with `depth` lives relative to the current one.
+    #[allow(clippy::cast_possible_wrap)]
+    const fn frame_offset(&self, depth: usize) -> isize {
+        depth as isize - self.scope_depth as isize
+    }
+
     #[allow(clippy::cast_possible_wrap)]
     fn patch_jump(chunk: &mut Chunk, start: usize, end: usize) {
         match &mut chunk[start].1 {
             OpCode::Jump(offset) => *offset = (end as isize) - (start as isize),
-            OpCode::JumpIfFalse(offset) => *offset = end - start,
+            OpCode::JumpIfFalse(offset) | OpCode::IterNext(offset) => *offset = end - start,
             _ => {}
         }
     }
 
+    #[allow(clippy::cast_possible_wrap)]
+    fn jump_back(chunk: &mut Chunk, line: usize, start: usize) {
+        let offset = (start as isize) - (chunk.len() as isize) - 1;
+
+        chunk.push(line, OpCode::Jump(offset));
+    }
+
     fn create_const(chunk: &mut Chunk, value: Value) -> usize {
         let constant = chunk.constants();
 
@@ -0,0 +1,88 @@
+use crate::prelude::*;
+
+/// Resolves type declared by `statement`.
+///
+/// # Errors
+///
+/// Returns `CompileError::UnknownType` if type of any payload field is not declared
+pub fn enum_type(compiler: &Compiler, statement: &EnumStatement) -> CompileResult<EnumType> {
+    let mut variants = Vec::new();
+
+    for variant in &statement.variants.value {
+        let EnumVariant { name, fields } = &variant.value;
+
+        let fields = match fields {
+            EnumVariantFields::Unit => VariantFields::Unit,
+            EnumVariantFields::Tuple(fields) => VariantFields::Tuple(
+                fields
+                    .iter()
+                    .map(|field| {
+                        compiler.resolve_field_type(
+                            &statement.name.value.0,
+                            field.value.clone(),
+                            field.span,
+                        )
+                    })
+                    .collect::<CompileResult<_>>()?,
+            ),
+            EnumVariantFields::Struct(fields) => VariantFields::Struct(
+                fields
+                    .iter()
+                    .map(|field| {
+                        let StructField { name, ty } = &field.value;
+
+                        Ok((
+                            name.value.0.clone(),
+                            compiler.resolve_field_type(
+                                &statement.name.value.0,
+                                ty.value.clone(),
+                                ty.span,
+                            )?,
+                        ))
+                    })
+                    .collect::<CompileResult<_>>()?,
+            ),
+        };
+
+        variants.push((name.value.0.clone(), fields));
+    }
+
+    Ok(EnumType {
+        name: statement.name.to_string(),
+        variants,
+    })
+}
+
+impl Compile for EnumStatement {
+    fn compile(
+        self,
+        compiler: &mut crate::Compiler,
+        span: Span,
+        chunk: &mut Chunk,
+    ) -> crate::CompileResult<()> {
+        let enumeration = enum_type(compiler, &self)?;
+
+        let constant = Compiler::create_const(
+            chunk,
+            Value::object(Object::Enum(Enum {
+                name: self.name.to_string(),
+                variants: enumeration
+                    .variants
+                    .iter()
+                    .map(|(name, _)| name.clone())
+                    .collect(),
+            })),
+        );
+
+        let ty = Type::Enum(enumeration);
+
+        compiler.types.insert(self.name.to_string(), ty.clone());
+
+        let variable = compiler.get_or_create_var(self.name.value.0, ty, false, Some(span));
+
+        chunk.push(span.line, OpCode::LoadConst(constant));
+        compiler.store_slot(chunk, span.line, variable);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use crate::{CompileAssign, GetType, prelude::*};
+
+impl Compile for ArrayExpr {
+    fn compile(self, compiler: &mut Compiler, span: Span, chunk: &mut Chunk) -> CompileResult<()> {
+        self.get_type(compiler, span)?;
+
+        let len = self.values.len();
+
+        for value in self.values {
+            let (span, value) = value.unpack();
+
+            value.compile(compiler, span, chunk, None)?;
+        }
+
+        chunk.push(span.line, OpCode::CreateArray(len));
+
+        Ok(())
+    }
+}
+
+impl GetType for ArrayExpr {
+    fn get_type(&self, compiler: &Compiler, span: Span) -> CompileResult<Type> {
+        let types = self
+            .values
+            .iter()
+            .map(|value| value.get_type(compiler, span))
+            .collect::<CompileResult<Vec<_>>>()?;
+
+        // element type of empty array is unknown, it fits wherever array is expected
+        let Some(first) = types.first() else {
+            return Ok(Type::Array(Box::new(Type::Any)));
+        };
+
+        // empty arrays fit into array of any type, so the type is taken from the first known one
+        let item = types
+            .iter()
+            .find(|ty| !ty.has_unknown())
+            .unwrap_or(first)
+            .clone();
+
+        for (value, ty) in self.values.iter().zip(types) {
+            if !item.compare(&ty) {
+                return Err(CompileError::TypeExpected {
+                    expected: item,
+                    found: ty,
+                    at: value.span,
+                });
+            }
+        }
+
+        Ok(Type::Array(Box::new(item)))
+    }
+}
@@ -1,7 +1,9 @@
-use crate::{CompileAssign, GetType, prelude::*};
+use crate::{Assignment, CompileAssign, GetType, prelude::*};
 
 impl Compile for BinaryExpression {
     fn compile(self, compiler: &mut Compiler, span: Span, chunk: &mut Chunk) -> CompileResult<()> {
+        self.get_type(compiler, span)?;
+
         if matches!(self.operator.value, Operator::And | Operator::Or) {
             {
                 let (span, value) = self.lhs.unpack();
@@ -9,39 +11,46 @@ impl Compile for BinaryExpression {
                 value.compile(compiler, span, chunk, None)?;
             }
 
-            match self.operator.value {
-                Operator::And => {
-                    let (start, end) = compiler.track_position(chunk, move |compiler, chunk| {
-                        chunk.push(span.line, OpCode::JumpIfFalse(0));
-                        chunk.push(span.line, OpCode::Pop);
+            // `JumpIfFalse` pops the condition, so the short-circuited result is pushed again
+            let line = span.line;
+            let is_and = self.operator.value == Operator::And;
+            let (span, rhs) = self.rhs.unpack();
 
-                        let (span, value) = self.rhs.unpack();
+            let condition = chunk.len();
 
-                        value.compile(compiler, span, chunk, None)
-                    })?;
+            chunk.push(line, OpCode::JumpIfFalse(0));
 
-                    Compiler::patch_jump(chunk, start, end);
-                }
-                Operator::Or => {
-                    let (start, end) = compiler.track_position(chunk, move |compiler, chunk| {
-                        chunk.push(span.line, OpCode::JumpIfFalse(0));
-                        chunk.push(span.line, OpCode::Jump(0));
-                        chunk.push(span.line, OpCode::Pop);
+            if is_and {
+                rhs.clone().compile(compiler, span, chunk, None)?;
+            } else {
+                Compiler::compile_const(chunk, line, Value::Boolean(true));
+            }
 
-                        let (span, value) = self.rhs.unpack();
+            let exit = chunk.len();
 
-                        value.compile(compiler, span, chunk, None)
-                    })?;
+            chunk.push(line, OpCode::Jump(0));
 
-                    Compiler::patch_jump(chunk, start, start + 1);
-                    Compiler::patch_jump(chunk, start + 1, end);
-                }
-                _ => unreachable!(),
+            Compiler::patch_jump(chunk, condition, exit);
+
+            if is_and {
+                Compiler::compile_const(chunk, line, Value::Boolean(false));
+            } else {
+                rhs.compile(compiler, span, chunk, None)?;
             }
-        } else if self.operator.value == Operator::Assign {
-            let (span, value) = self.lhs.unpack();
 
-            value.compile(compiler, span, chunk, Some(self.rhs))?;
+            Compiler::patch_jump(chunk, exit, chunk.len() - 1);
+        } else if self.operator.value.precedence() == Precedence::PAssign {
+            let (span, value) = self.lhs.unpack();
+            let assignment = Assignment {
+                operator: self
+                    .operator
+                    .value
+                    .compound()
+                    .map(|operator| self.operator.span.wrap(operator)),
+                value: self.rhs,
+            };
+
+            value.compile(compiler, span, chunk, Some(assignment))?;
         } else {
             {
                 let (span, value) = self.lhs.unpack();
@@ -58,19 +67,31 @@ impl Compile for BinaryExpression {
                 Operator::Sub => chunk.push(self.operator.span.line, OpCode::Sub),
                 Operator::Mul => chunk.push(self.operator.span.line, OpCode::Mul),
                 Operator::Div => chunk.push(self.operator.span.line, OpCode::Div),
+                Operator::Rem => chunk.push(self.operator.span.line, OpCode::Rem),
+                Operator::BitAnd => chunk.push(self.operator.span.line, OpCode::BitAnd),
+                Operator::BitOr => chunk.push(self.operator.span.line, OpCode::BitOr),
+                Operator::BitXor => chunk.push(self.operator.span.line, OpCode::BitXor),
+                Operator::Shl => chunk.push(self.operator.span.line, OpCode::Shl),
+                Operator::Shr => chunk.push(self.operator.span.line, OpCode::Shr),
                 Operator::Equal => {
                     chunk.push(self.operator.span.line, OpCode::Equal);
                 }
                 Operator::NotEqual => {
                     chunk.push(self.operator.span.line, OpCode::Equal);
-                    chunk.push(self.operator.span.line, OpCode::Negate);
+                    chunk.push(self.operator.span.line, OpCode::Not);
                 }
                 Operator::LessThan => {
                     chunk.push(self.operator.span.line, OpCode::Less);
                 }
+                Operator::LessEqual => {
+                    chunk.push(self.operator.span.line, OpCode::LessEqual);
+                }
                 Operator::GreaterThan => {
                     chunk.push(self.operator.span.line, OpCode::Greater);
                 }
+                Operator::GreaterEqual => {
+                    chunk.push(self.operator.span.line, OpCode::GreaterEqual);
+                }
                 _ => unreachable!(),
             }
         }
@@ -81,17 +102,23 @@ impl Compile for BinaryExpression {
 
 impl GetType for BinaryExpression {
     fn get_type(&self, compiler: &Compiler, span: Span) -> CompileResult<Type> {
+        // assignment leaves nothing on the stack, its operands are checked when it's compiled
+        if self.operator.value.precedence() == Precedence::PAssign {
+            return Ok(Type::None);
+        }
+
         let primary = self.lhs.get_type(compiler, span)?;
         let maybe_primary = self.rhs.get_type(compiler, span)?;
 
         if matches!(
             self.operator.value,
-            Operator::Add
-                | Operator::Sub
+            Operator::Sub
                 | Operator::Mul
                 | Operator::Div
-                | Operator::GreaterThan
-                | Operator::LessThan
+                | Operator::Rem
+                | Operator::SubAssign
+                | Operator::MulAssign
+                | Operator::DivAssign
         ) && !matches!(primary, Type::Float | Type::Integer)
         {
             return Err(CompileError::OneOfTypeExpected {
@@ -101,6 +128,36 @@ impl GetType for BinaryExpression {
             });
         }
 
+        // strings are concatenated and compared lexicographically
+        if matches!(
+            self.operator.value,
+            Operator::Add
+                | Operator::AddAssign
+                | Operator::GreaterThan
+                | Operator::GreaterEqual
+                | Operator::LessThan
+                | Operator::LessEqual
+        ) && !matches!(primary, Type::Float | Type::Integer | Type::String)
+        {
+            return Err(CompileError::OneOfTypeExpected {
+                expected: vec![Type::Float, Type::Integer, Type::String],
+                found: primary,
+                at: self.lhs.span,
+            });
+        }
+
+        if matches!(
+            self.operator.value,
+            Operator::BitAnd | Operator::BitOr | Operator::BitXor | Operator::Shl | Operator::Shr
+        ) && primary != Type::Integer
+        {
+            return Err(CompileError::TypeExpected {
+                expected: Type::Integer,
+                found: primary,
+                at: self.lhs.span,
+            });
+        }
+
         if matches!(self.operator.value, Operator::And | Operator::Or) && primary != Type::Boolean {
             return Err(CompileError::TypeExpected {
                 expected: Type::Boolean,
@@ -109,7 +166,11 @@ impl GetType for BinaryExpression {
             });
         }
 
-        if primary != maybe_primary {
+        // optional values can be compared with `none`
+        let comparable = matches!(self.operator.value, Operator::Equal | Operator::NotEqual)
+            && (primary.compare(&maybe_primary) || maybe_primary.compare(&primary));
+
+        if !comparable && primary != maybe_primary {
             return Err(CompileError::TypeExpected {
                 expected: primary,
                 found: maybe_primary,
@@ -119,7 +180,12 @@ impl GetType for BinaryExpression {
 
         if matches!(
             self.operator.value,
-            Operator::Equal | Operator::GreaterThan | Operator::LessThan | Operator::NotEqual
+            Operator::Equal
+                | Operator::NotEqual
+                | Operator::GreaterThan
+                | Operator::GreaterEqual
+                | Operator::LessThan
+                | Operator::LessEqual
         ) {
             return Ok(Type::Boolean);
         }
@@ -9,10 +9,26 @@ impl Compile for FunctionCall {
             output_type: _,
         }) = target_ty
         {
+            let method = target_method(&self.target.value, compiler, span)?;
+
             {
                 let (span, value) = self.target.unpack();
 
-                value.compile(compiler, span, chunk, None)?;
+                if let Some(method) = &method
+                    && let Expression::Index(index) = value
+                {
+                    Compiler::compile_const(
+                        chunk,
+                        span.line,
+                        Value::object(Object::NativeFunction(method.clone())),
+                    );
+
+                    let (span, value) = index.target.unpack();
+
+                    value.compile(compiler, span, chunk, None)?;
+                } else {
+                    value.compile(compiler, span, chunk, None)?;
+                }
             }
 
             if args.len() != self.args.value.len() {
@@ -26,10 +42,10 @@ impl Compile for FunctionCall {
 
             let arg_count = self.args.value.len();
 
-            for (provided_arg, expected_arg) in self.args.value.into_iter().zip(args.into_iter()) {
+            for (provided_arg, expected_arg) in self.args.value.into_iter().zip(args) {
                 let ty = provided_arg.get_type(compiler, span)?;
 
-                if !ty.compare(&expected_arg) {
+                if !expected_arg.compare(&ty) {
                     return Err(CompileError::TypeExpected {
                         expected: expected_arg,
                         found: ty,
@@ -42,7 +58,10 @@ impl Compile for FunctionCall {
                 value.compile(compiler, span, chunk, None)?;
             }
 
-            chunk.push(span.line, OpCode::Call(arg_count));
+            chunk.push(
+                span.line,
+                OpCode::Call(arg_count + usize::from(method.is_some())),
+            );
 
             Ok(())
         } else {
@@ -58,6 +77,21 @@ impl Compile for FunctionCall {
     }
 }
 
+/// Returns built-in method if call target is `value.method`.
+fn target_method(
+    target: &Expression,
+    compiler: &Compiler,
+    span: Span,
+) -> CompileResult<Option<NativeFunction>> {
+    if let Expression::Index(index) = target
+        && let IndexKind::Ident(name) = &index.index.value
+    {
+        Ok(method(&index.target.get_type(compiler, span)?, name))
+    } else {
+        Ok(None)
+    }
+}
+
 impl GetType for FunctionCall {
     fn get_type(&self, compiler: &Compiler, span: Span) -> CompileResult<Type> {
         let ty = self.target.get_type(compiler, span)?;
@@ -0,0 +1,49 @@
+use crate::{CompileAssign, GetType, prelude::*};
+
+/// Returns conversion performed by `expression`, `None` if the value already has target type,
+/// and the target type.
+fn conversion(
+    expression: &CastExpression,
+    compiler: &Compiler,
+    span: Span,
+) -> CompileResult<(Option<Cast>, Type)> {
+    let from = expression.value.get_type(compiler, span)?;
+    let to = compiler.resolve_type(expression.ty.value.clone(), expression.ty.span)?;
+
+    let cast = match (&from, &to) {
+        (from, to) if from == to => None,
+        (Type::Integer, Type::Float) => Some(Cast::IntToFloat),
+        (Type::Float, Type::Integer) => Some(Cast::FloatToInt),
+        (Type::Integer | Type::Float, Type::String) => Some(Cast::NumberToString),
+        (Type::Boolean, Type::Integer) => Some(Cast::BoolToInt),
+        _ => {
+            return Err(CompileError::InvalidCast { from, to, at: span });
+        }
+    };
+
+    Ok((cast, to))
+}
+
+impl Compile for CastExpression {
+    fn compile(self, compiler: &mut Compiler, span: Span, chunk: &mut Chunk) -> CompileResult<()> {
+        let (cast, _) = conversion(&self, compiler, span)?;
+
+        {
+            let (span, value) = self.value.unpack();
+
+            value.compile(compiler, span, chunk, None)?;
+        }
+
+        if let Some(cast) = cast {
+            chunk.push(self.ty.span.line, OpCode::Cast(cast));
+        }
+
+        Ok(())
+    }
+}
+
+impl GetType for CastExpression {
+    fn get_type(&self, compiler: &Compiler, span: Span) -> CompileResult<Type> {
+        conversion(self, compiler, span).map(|(_, ty)| ty)
+    }
+}
@@ -0,0 +1,47 @@
+use crate::{CompileAssign, GetType, prelude::*};
+
+impl Compile for FormattedString {
+    fn compile(self, compiler: &mut Compiler, span: Span, chunk: &mut Chunk) -> CompileResult<()> {
+        self.get_type(compiler, span)?;
+
+        let len = self.parts.len();
+
+        for part in self.parts {
+            match part {
+                FormattedPart::String(value) => {
+                    Compiler::compile_const(chunk, span.line, Value::from(value));
+                }
+                FormattedPart::Value(value) => {
+                    let (span, value) = (*value).unpack();
+
+                    value.compile(compiler, span, chunk, None)?;
+                }
+            }
+        }
+
+        chunk.push(span.line, OpCode::Format(len));
+
+        Ok(())
+    }
+}
+
+impl GetType for FormattedString {
+    fn get_type(&self, compiler: &Compiler, span: Span) -> CompileResult<Type> {
+        for part in &self.parts {
+            if let FormattedPart::Value(value) = part {
+                let ty = value.get_type(compiler, span)?;
+
+                // expressions without value don't leave anything on the stack to format
+                if ty == Type::None {
+                    return Err(CompileError::TypeExpected {
+                        expected: Type::Any,
+                        found: ty,
+                        at: value.span,
+                    });
+                }
+            }
+        }
+
+        Ok(Type::String)
+    }
+}
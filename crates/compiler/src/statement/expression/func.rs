@@ -0,0 +1,33 @@
+use crate::{
+    GetType,
+    prelude::*,
+    statement::func::{compile_function, signature},
+};
+
+impl Compile for FuncExpression {
+    fn compile(self, compiler: &mut Compiler, span: Span, chunk: &mut Chunk) -> CompileResult<()> {
+        // anonymous function can't reference itself, its variable has no name
+        compile_function(
+            compiler,
+            chunk,
+            String::new(),
+            self.args,
+            self.output_type.as_ref(),
+            self.body,
+            span,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl GetType for FuncExpression {
+    fn get_type(&self, compiler: &Compiler, _: Span) -> CompileResult<Type> {
+        let (args, output) = signature(compiler, &self.args.value, self.output_type.as_ref())?;
+
+        Ok(Type::Function(FunctionType {
+            args,
+            output_type: Box::new(output),
+        }))
+    }
+}
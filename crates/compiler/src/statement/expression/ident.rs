@@ -1,6 +1,6 @@
 use tapt_parser::Ident;
 
-use crate::{CompileAssign, GetType, prelude::*};
+use crate::{Assignment, CompileAssign, GetType, prelude::*};
 
 impl CompileAssign for Ident {
     fn compile(
@@ -8,46 +8,22 @@ impl CompileAssign for Ident {
         compiler: &mut Compiler,
         span: Span,
         chunk: &mut Chunk,
-        assign_value: Option<Positioned<Expression>>,
+        assignment: Option<Assignment>,
     ) -> CompileResult<()> {
-        let (slot, variable) = compiler.get_var(span, &self.0)?;
-
-        if let Some(value) = assign_value {
-            if variable.mutable {
-                let value_type = value.get_type(compiler, span)?;
-
-                if value_type != variable.ty {
-                    return Err(CompileError::TypeExpected {
-                        expected: variable.ty.clone(),
-                        found: value_type,
-                        at: value.span,
-                    });
-                }
-
-                let depth = variable.depth;
-
-                {
-                    let (span, value) = value.unpack();
-
-                    value.compile(compiler, span, chunk, None)?;
-                }
-
-                chunk.push(span.line, OpCode::SetLocal(Some(depth), slot));
-            } else {
-                return Err(CompileError::ImmutableVariable {
-                    name: self.to_string(),
-                    accessed_at: span,
-                    declared_at: variable.span,
-                });
+        if let Some(assignment) = assignment {
+            let ty = compiler.get_mutable_var(span, &self.0)?.ty.clone();
+
+            assignment.check(compiler, span, &ty)?;
+
+            if assignment.operator.is_some() {
+                compiler.load_var(chunk, span, &self.0)?;
             }
+
+            assignment.compile(compiler, chunk)?;
+
+            compiler.store_var(chunk, span, &self.0)?;
         } else {
-            chunk.push(
-                span.line,
-                OpCode::GetLocal(
-                    variable.depth as isize - compiler.scope_depth as isize,
-                    slot,
-                ),
-            );
+            compiler.load_var(chunk, span, &self.0)?;
         }
 
         Ok(())
@@ -58,6 +34,6 @@ impl GetType for Ident {
     fn get_type(&self, compiler: &Compiler, span: Span) -> CompileResult<Type> {
         compiler
             .get_var(span, &self.0)
-            .map(|(_, value)| value.ty.clone())
+            .map(|value| value.ty.clone())
     }
 }
@@ -1,4 +1,4 @@
-use crate::{CompileAssign, CompilePositioned, GetType, prelude::*};
+use crate::{CompileAssign, CompilePositioned, GetType, prelude::*, statement::block_returns};
 
 impl Compile for IfElseExpression {
     fn compile(self, compiler: &mut Compiler, span: Span, chunk: &mut Chunk) -> CompileResult<()> {
@@ -48,6 +48,12 @@ impl Compile for IfElseExpression {
 
 impl GetType for IfElseExpression {
     fn get_type(&self, compiler: &Compiler, span: Span) -> CompileResult<Type> {
-        self.block.get_type(compiler, span)
+        match &self.else_block {
+            // branch leaving the function doesn't produce a value
+            Some(else_block) if block_returns(&self.block.value) => {
+                else_block.get_type(compiler, span)
+            }
+            _ => self.block.get_type(compiler, span),
+        }
     }
 }
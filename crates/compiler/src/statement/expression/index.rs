@@ -1,14 +1,179 @@
-use crate::{CompileAssign, GetType, prelude::*};
+use crate::{Assignment, CompileAssign, GetType, prelude::*};
+
+/// Returns type of array element, checking that `index` is an integer.
+fn array_item(
+    compiler: &Compiler,
+    span: Span,
+    target: Type,
+    index: &Expression,
+    index_span: Span,
+) -> CompileResult<Type> {
+    let Type::Array(item) = target else {
+        return Err(CompileError::TypeExpected {
+            expected: Type::Array(Box::new(Type::Any)),
+            found: target,
+            at: span,
+        });
+    };
+
+    let ty = index.get_type(compiler, span)?;
+
+    if ty == Type::Integer {
+        Ok(*item)
+    } else {
+        Err(CompileError::TypeExpected {
+            expected: Type::Integer,
+            found: ty,
+            at: index_span,
+        })
+    }
+}
+
+/// Returns enum and name of the variant if expression is `Enum.Variant`.
+fn variant_path<'a>(
+    compiler: &'a Compiler,
+    expression: &'a IndexExpression,
+) -> Option<(&'a EnumType, &'a Ident)> {
+    if let Expression::Ident(target) = &expression.target.value
+        && let Some(Type::Enum(enumeration)) = compiler.types.get(&target.0)
+        && let IndexKind::Ident(name) = &expression.index.value
+    {
+        Some((enumeration, name))
+    } else {
+        None
+    }
+}
+
+/// Returns tag of variant without payload, variants with it are created by `new`.
+fn unit_variant(enumeration: &EnumType, name: &Ident, at: Span) -> CompileResult<usize> {
+    let Some(tag) = enumeration
+        .variants
+        .iter()
+        .position(|(variant, _)| variant == &**name)
+    else {
+        return Err(CompileError::PropertyNotExist {
+            target: enumeration.name.clone(),
+            property: name.to_string(),
+            defined_at: None,
+            at,
+        });
+    };
+
+    let variant = format!("{}.{name}", enumeration.name);
+
+    match &enumeration.variants[tag].1 {
+        VariantFields::Unit => Ok(tag),
+        VariantFields::Tuple(_) => Err(CompileError::VariantWithoutNew {
+            constructor: format!("new {variant}(...)"),
+            variant,
+            at,
+        }),
+        VariantFields::Struct(_) => Err(CompileError::VariantWithoutNew {
+            constructor: format!("new {variant} {{ ... }}"),
+            variant,
+            at,
+        }),
+    }
+}
+
+/// Returns position and type of the field accessed by `index` on instance of `target`.
+fn field(target: &Type, index: &IndexKind) -> Option<(usize, Type)> {
+    match (target, index) {
+        (Type::Record(RecordType { fields, .. }), IndexKind::Number(index)) => {
+            fields.get(*index).map(|ty| (*index, ty.clone()))
+        }
+        (
+            Type::Struct(StructType { fields, .. }) | Type::Object(ObjectType { fields }),
+            IndexKind::Ident(name),
+        ) => fields
+            .iter()
+            .position(|(field_name, _)| field_name == &**name)
+            .map(|index| (index, fields[index].1.clone())),
+        _ => None,
+    }
+}
+
+fn property_not_exist(target: &Type, index: &Positioned<IndexKind>) -> CompileError {
+    CompileError::PropertyNotExist {
+        target: target.to_string(),
+        property: index.value.to_string(),
+        defined_at: None,
+        at: index.span,
+    }
+}
+
+/// Returns variable holding the value assigned place belongs to, like `me` of `me.pets[0].age`.
+fn root_variable(target: &Positioned<Expression>) -> Option<(Span, &Ident)> {
+    match &target.value {
+        Expression::Ident(name) => Some((target.span, name)),
+        Expression::Index(value) => root_variable(&value.target),
+        _ => None,
+    }
+}
+
+/// Loads field `index` of the instance on top of the stack, or assigns to it if `assignment` is
+/// given.
+fn compile_field(
+    compiler: &mut Compiler,
+    span: Span,
+    chunk: &mut Chunk,
+    index: usize,
+    ty: &Type,
+    assignment: Option<Assignment>,
+) -> CompileResult<()> {
+    if let Some(assignment) = assignment {
+        assignment.check(compiler, span, ty)?;
+
+        if assignment.operator.is_some() {
+            chunk.push(span.line, OpCode::Copy);
+            chunk.push(span.line, OpCode::GetProperty(index));
+        }
+
+        assignment.compile(compiler, chunk)?;
+
+        chunk.push(span.line, OpCode::SetProperty(index));
+    } else {
+        chunk.push(span.line, OpCode::GetProperty(index));
+    }
+
+    Ok(())
+}
 
 impl CompileAssign for IndexExpression {
+    #[allow(clippy::too_many_lines)]
     fn compile(
         self,
         compiler: &mut Compiler,
         span: Span,
         chunk: &mut Chunk,
-        assign_value: Option<Positioned<Expression>>,
+        assignment: Option<Assignment>,
     ) -> CompileResult<()> {
+        if let Some((enumeration, name)) = variant_path(compiler, &self) {
+            let tag = unit_variant(enumeration, name, self.index.span)?;
+
+            Compiler::compile_const(
+                chunk,
+                self.index.span.line,
+                Value::object(Object::Variant(Variant {
+                    enum_name: enumeration.name.clone(),
+                    name: name.to_string(),
+                    tag,
+                    fields: Vec::new(),
+                })),
+            );
+
+            return Ok(());
+        }
+
+        // elements and fields of constants can't be assigned either
+        if assignment.is_some()
+            && let Some((span, name)) = root_variable(&self.target)
+        {
+            compiler.get_mutable_var(span, &name.0)?;
+        }
+
         let target = self.target.get_type(compiler, span)?;
+        let target_span = self.target.span;
 
         {
             let (span, value) = self.target.unpack();
@@ -16,86 +181,69 @@ impl CompileAssign for IndexExpression {
             value.compile(compiler, span, chunk, None)?;
         }
 
-        if let Type::Record(RecordType { fields, .. }) = target {
-            if let IndexKind::Number(index) = &self.index.value {
-                if *index > fields.len() {
-                    todo!()
-                } else {
-                    if let Some(assign_value) = assign_value {
-                        let (span, value) = assign_value.unpack();
+        if let IndexKind::Expr(index) = self.index.value {
+            let item = array_item(compiler, target_span, target, &index, self.index.span)?;
 
-                        value.compile(compiler, span, chunk, None)?;
+            index.compile(compiler, self.index.span, chunk, None)?;
 
-                        chunk.push(self.index.span.line, OpCode::SetProperty(*index));
-                    } else {
-                        chunk.push(self.index.span.line, OpCode::GetProperty(*index));
-                    }
+            if let Some(assignment) = assignment {
+                assignment.check(compiler, span, &item)?;
 
-                    Ok(())
-                }
-            } else {
-                todo!()
-            }
-        } else if let Type::Struct(structure) = target {
-            if let IndexKind::Ident(name) = &self.index.value {
-                if let Some(index) = structure
-                    .fields
-                    .iter()
-                    .position(|(field_name, _)| field_name == &**name)
-                {
-                    if let Some(assign_value) = assign_value {
-                        let (span, value) = assign_value.unpack();
-
-                        value.compile(compiler, span, chunk, None)?;
-
-                        chunk.push(self.index.span.line, OpCode::SetProperty(index));
-                    } else {
-                        chunk.push(self.index.span.line, OpCode::GetProperty(index));
-                    }
-
-                    Ok(())
-                } else {
-                    todo!()
+                if assignment.operator.is_some() {
+                    chunk.push(self.index.span.line, OpCode::CopyPair);
+                    chunk.push(self.index.span.line, OpCode::GetIndex);
                 }
+
+                assignment.compile(compiler, chunk)?;
+
+                chunk.push(self.index.span.line, OpCode::SetIndex);
             } else {
-                todo!()
+                chunk.push(self.index.span.line, OpCode::GetIndex);
             }
+
+            return Ok(());
+        }
+
+        if let Some((index, ty)) = field(&target, &self.index.value) {
+            compile_field(compiler, self.index.span, chunk, index, &ty, assignment)
+        } else if let IndexKind::Ident(name) = &self.index.value
+            && method(&target, name).is_some()
+        {
+            Err(CompileError::MethodNotCalled {
+                name: name.to_string(),
+                at: self.index.span,
+            })
         } else {
-            todo!()
+            Err(property_not_exist(&target, &self.index))
         }
     }
 }
 
 impl GetType for IndexExpression {
     fn get_type(&self, compiler: &Compiler, span: Span) -> CompileResult<Type> {
+        if let Some((enumeration, name)) = variant_path(compiler, self) {
+            unit_variant(enumeration, name, self.index.span)?;
+
+            return Ok(Type::Enum(enumeration.clone()));
+        }
+
         let target = self.target.get_type(compiler, span)?;
 
-        if let Type::Record(RecordType { fields, .. }) = target {
-            if let IndexKind::Number(index) = &self.index.value {
-                if *index > fields.len() {
-                    todo!()
-                } else {
-                    Ok(fields[*index].clone())
-                }
-            } else {
-                todo!()
-            }
-        } else if let Type::Struct(structure) = target {
-            if let IndexKind::Ident(name) = &self.index.value {
-                if let Some((_, value)) = structure
-                    .fields
-                    .iter()
-                    .find(|(field_name, _)| field_name == &**name)
-                {
-                    Ok(value.clone())
-                } else {
-                    todo!()
-                }
-            } else {
-                todo!()
-            }
+        if let IndexKind::Expr(index) = &self.index.value {
+            return array_item(compiler, self.target.span, target, index, self.index.span);
+        }
+
+        if let Some((_, ty)) = field(&target, &self.index.value) {
+            Ok(ty)
+        } else if let IndexKind::Ident(name) = &self.index.value
+            && let Some(method) = method(&target, name)
+        {
+            Ok(Type::Function(FunctionType {
+                args: method.meta.args,
+                output_type: Box::new(method.meta.output),
+            }))
         } else {
-            todo!()
+            Err(property_not_exist(&target, &self.index))
         }
     }
 }
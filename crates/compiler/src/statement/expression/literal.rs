@@ -2,8 +2,8 @@ use crate::{GetType, prelude::*};
 
 impl Compile for Literal {
     fn compile(self, _: &mut Compiler, span: Span, chunk: &mut Chunk) -> CompileResult<()> {
-        Compiler::compile_const(chunk, span.line as usize, match self {
-            Self::Range(_) => todo!(),
+        Compiler::compile_const(chunk, span.line, match self {
+            Self::None => Value::None,
             Self::Number(value) => match value {
                 Number::Float(value) => Value::Float(value),
                 Number::Int(value) => Value::Integer(value),
@@ -25,7 +25,7 @@ impl GetType for Literal {
             },
             Self::String(_) => Type::String,
             Self::Boolean(_) => Type::Boolean,
-            Self::Range(_) => todo!(), // no ranges
+            Self::None => Type::None,
         })
     }
 }
@@ -1,8 +1,161 @@
-use crate::{CompileAssign, GetType, prelude::*};
+use crate::{
+    CompileAssign, GetType,
+    prelude::*,
+    statement::{
+        expression_returns,
+        pattern::{Place, compile_pattern, fields_irrefutable, is_irrefutable, pattern_bindings},
+    },
+};
+
+/// Tracks which values of the match target are handled by arms.
+struct Coverage {
+    /// Enum variants or boolean values with flag whether they are handled, `None` if target can
+    /// have any value.
+    values: Option<Vec<(String, bool)>>,
+    catch_all: bool,
+}
+
+impl Coverage {
+    fn new(target_type: &Type) -> Self {
+        let values: Option<Vec<String>> = match target_type {
+            Type::Boolean => Some(vec!["true".into(), "false".into()]),
+            Type::Enum(enumeration) => Some(
+                enumeration
+                    .variants
+                    .iter()
+                    .map(|(name, _)| name.clone())
+                    .collect(),
+            ),
+            _ => None,
+        };
+
+        Self {
+            values: values.map(|values| values.into_iter().map(|value| (value, false)).collect()),
+            catch_all: false,
+        }
+    }
+
+    fn is_exhaustive(&self) -> bool {
+        self.catch_all
+            || self
+                .values
+                .as_ref()
+                .is_some_and(|values| values.iter().all(|(_, handled)| *handled))
+    }
+
+    /// Marks values matched by `case` as handled, returns `false` if they were handled before.
+    fn cover(&mut self, case: &Pattern) -> bool {
+        let name = match case {
+            _ if is_irrefutable(case) => {
+                self.catch_all = true;
+
+                return true;
+            }
+            Pattern::Or(patterns) => {
+                let mut covered = false;
+
+                for pattern in patterns {
+                    covered |= self.cover(&pattern.value);
+                }
+
+                return covered;
+            }
+            Pattern::Instance(instance) => match &instance.variant {
+                Some(variant) if fields_irrefutable(&instance.fields.value) => variant.to_string(),
+                _ => return true,
+            },
+            Pattern::Value(expression) => match &**expression {
+                Expression::Literal(Literal::Boolean(value)) => value.to_string(),
+                Expression::Index(index) => match &index.index.value {
+                    IndexKind::Ident(name) => name.to_string(),
+                    _ => return true,
+                },
+                _ => return true,
+            },
+            Pattern::Wildcard | Pattern::Binding(_) => return true,
+        };
+
+        match self
+            .values
+            .as_mut()
+            .and_then(|values| values.iter_mut().find(|(value, _)| value == &name))
+        {
+            Some((_, handled)) => !std::mem::replace(handled, true),
+            None => true,
+        }
+    }
+
+    fn missing(&self) -> Vec<String> {
+        match &self.values {
+            _ if self.catch_all => Vec::new(),
+            Some(values) => values
+                .iter()
+                .filter(|(_, handled)| !handled)
+                .map(|(value, _)| value.clone())
+                .collect(),
+            None => vec!["_".into()],
+        }
+    }
+}
+
+/// Checks that `pattern` matches `none`.
+fn matches_none(pattern: &Pattern) -> bool {
+    match pattern {
+        Pattern::Value(expression) => matches!(**expression, Expression::Literal(Literal::None)),
+        Pattern::Or(patterns) => patterns.iter().any(|pattern| matches_none(&pattern.value)),
+        _ => false,
+    }
+}
+
+/// Returns type of the target seen by each arm.
+///
+/// Once an arm handles `none`, the following arms see value of optional target unwrapped.
+fn arm_types(target_type: &Type, variants: &[Positioned<MatchVariant>]) -> Vec<Type> {
+    let mut ty = target_type.clone();
+
+    variants
+        .iter()
+        .map(|variant| {
+            let current = ty.clone();
+
+            if variant.value.guard.is_none()
+                && matches_none(&variant.value.case.value)
+                && let Type::Optional(value) = current.clone()
+            {
+                ty = *value;
+            }
+
+            current
+        })
+        .collect()
+}
 
 impl Compile for MatchExpression {
+    #[allow(clippy::too_many_lines)]
     fn compile(self, compiler: &mut Compiler, span: Span, chunk: &mut Chunk) -> CompileResult<()> {
         let target_type = self.target.get_type(compiler, span)?;
+        let output = self.get_type(compiler, span)?;
+
+        let mut coverage = Coverage::new(&target_type);
+
+        for variant in &self.variants {
+            let variant = &variant.value;
+
+            // arms with guard may not match, so they don't handle anything
+            if coverage.is_exhaustive()
+                || (variant.guard.is_none() && !coverage.cover(&variant.case.value))
+            {
+                compiler.warnings.push(CompileWarning::UnreachablePattern {
+                    at: variant.case.span,
+                });
+            }
+        }
+
+        let missing = coverage.missing();
+
+        if !missing.is_empty() && output != Type::None {
+            return Err(CompileError::NonExhaustiveMatch { missing, at: span });
+        }
 
         {
             let (span, value) = self.target.unpack();
@@ -10,101 +163,128 @@ impl Compile for MatchExpression {
             value.compile(compiler, span, chunk, None)?;
         }
 
-        let mut jumps = Vec::new();
-
-        for variant in self.variants {
-            match variant.value.case.value {
-                MatchCase::Ident(ident) => {
-                    chunk.push(variant.span.line, OpCode::PushFrame);
+        // target lives in a variable of its own frame, so patterns can check it piece by piece
+        chunk.push(span.line, OpCode::PushFrame);
 
-                    compiler.push_scope();
+        compiler.push_scope();
 
-                    let slot = compiler.create_var(
-                        ident.to_string(),
-                        false,
-                        target_type,
-                        Some(variant.value.case.span),
-                    );
+        let target = compiler.create_var(String::new(), false, target_type.clone(), None);
+        let place = Place::variable(target);
+        let variables = compiler.variables.len();
 
-                    chunk.push(
-                        variant.value.case.span.line,
-                        OpCode::SetLocal(None, slot),
-                    );
+        compiler.store_slot(chunk, span.line, target);
 
-                    {
-                        let (span, value) = variant.value.then.unpack();
+        let mut jumps = Vec::new();
+        let arm_types = arm_types(&target_type, &self.variants);
 
-                        value.compile(compiler, span, chunk, None)?;
-                    }
+        for (variant, target_type) in self.variants.into_iter().zip(arm_types) {
+            let MatchVariant { case, guard, then } = variant.value;
+            let line = case.span.line;
+            let catch_all = guard.is_none() && is_irrefutable(&case.value);
+            let mut fails = Vec::new();
 
-                    chunk.push(variant.span.line, OpCode::Return);
+            for (name, ty) in pattern_bindings(compiler, &case, &target_type)? {
+                compiler.create_var(name, false, ty, Some(case.span));
+            }
 
-                    compiler.pop_scope(variant.span.line, chunk);
+            compile_pattern(compiler, chunk, &case, &target_type, &place, &mut fails)?;
 
-                    chunk.push(variant.span.line, OpCode::PopFrame);
+            if let Some(guard) = guard {
+                let ty = guard.get_type(compiler, guard.span)?;
 
-                    break; // ignore next variants :jokerge:
+                if ty != Type::Boolean {
+                    return Err(CompileError::TypeExpected {
+                        expected: Type::Boolean,
+                        found: ty,
+                        at: guard.span,
+                    });
                 }
-                MatchCase::Value(expression) => {
-                    let case_type = expression.get_type(compiler, span)?;
 
-                    if case_type != target_type {
-                        return Err(CompileError::TypeExpected {
-                            expected: target_type,
-                            found: case_type,
-                            at: variant.value.case.span,
-                        });
-                    }
+                let (span, value) = guard.unpack();
 
-                    chunk.push(variant.span.line, OpCode::Copy);
+                value.compile(compiler, span, chunk, None)?;
 
-                    expression.compile(compiler, variant.value.case.span, chunk, None)?;
+                fails.push(chunk.len());
 
-                    chunk.push(variant.value.case.span.line, OpCode::Equal);
+                chunk.push(line, OpCode::JumpIfFalse(0));
+            }
 
-                    let (start, end) = compiler.track_position(chunk, |compiler, chunk| {
-                        chunk.push(
-                            variant.value.case.span.line,
-                            OpCode::JumpIfFalse(0),
-                        );
-                        chunk.push(variant.value.case.span.line, OpCode::Pop);
+            {
+                let (span, value) = then.unpack();
 
-                        let (span, value) = variant.value.then.unpack();
+                value.compile(compiler, span, chunk, None)?;
+            }
 
-                        value.compile(compiler, span, chunk, None)?;
+            jumps.push(chunk.len());
 
-                        chunk.push(variant.value.case.span.line, OpCode::Jump(0));
+            chunk.push(line, OpCode::Jump(0));
 
-                        Ok(())
-                    })?;
+            let end = chunk.len() - 1;
 
-                    Compiler::patch_jump(chunk, start, end - 1);
+            for jump in fails {
+                Compiler::patch_jump(chunk, jump, end);
+            }
 
-                    jumps.push(end - 1);
-                }
+            // variables bound by the arm are not visible in the next ones
+            compiler.variables.truncate(variables);
+
+            if catch_all {
+                // arms after catch-all are unreachable
+                break;
             }
         }
 
+        if !missing.is_empty() {
+            // no arm matched
+            Compiler::compile_const(chunk, span.line, Value::None);
+        }
+
         let end = chunk.len() - 1;
 
         for jump in jumps {
             Compiler::patch_jump(chunk, jump, end);
         }
 
+        compiler.pop_scope(span.line, chunk);
+
+        chunk.push(span.line, OpCode::PopFrame);
+
         Ok(())
     }
 }
 
+/// Returns type of the arm, taking variables bound by its pattern into account.
+fn variant_type(
+    compiler: &Compiler,
+    target_type: &Type,
+    variant: &MatchVariant,
+    span: Span,
+) -> CompileResult<Type> {
+    let bindings = pattern_bindings(compiler, &variant.case, target_type)?;
+
+    variant.then.get_type(&compiler.scoped(bindings), span)
+}
+
 impl GetType for MatchExpression {
     fn get_type(&self, compiler: &Compiler, span: Span) -> CompileResult<Type> {
-        match self.variants.len() {
-            0 => Ok(Type::None),
-            1 => self.variants[0].value.then.get_type(compiler, span),
-            _ => {
-                let primary = self.variants[0].value.then.get_type(compiler, span)?;
-
-                for variant in &self.variants[1..] {
-                    let maybe_primary = variant.value.then.get_type(compiler, span)?;
+        let target_type = self.target.get_type(compiler, span)?;
+
+        // arms leaving the function don't produce a value
+        let variants = self
+            .variants
+            .iter()
+            .zip(arm_types(&target_type, &self.variants))
+            .filter(|(variant, _)| !expression_returns(&variant.value.then.value))
+            .collect::<Vec<_>>();
+
+        match variants.as_slice() {
+            [] => Ok(Type::None),
+            [(variant, target_type)] => variant_type(compiler, target_type, &variant.value, span),
+            [(first, first_type), rest @ ..] => {
+                let primary = variant_type(compiler, first_type, &first.value, span)?;
+
+                for (variant, target_type) in rest {
+                    let maybe_primary = variant_type(compiler, target_type, &variant.value, span)?;
 
                     if maybe_primary != primary {
                         return Err(CompileError::TypeExpected {
@@ -1,14 +1,21 @@
+mod array;
 mod binary;
 mod block;
 mod call;
+mod cast;
+mod formatted;
+mod func;
 mod ident;
 mod if_else;
 mod index;
 mod literal;
 mod matching;
 mod new;
+mod object;
+mod range;
+mod unary;
 
-use crate::{CompileAssign, GetType, prelude::*};
+use crate::{Assignment, CompileAssign, GetType, prelude::*};
 
 impl CompileAssign for Expression {
     fn compile(
@@ -16,12 +23,14 @@ impl CompileAssign for Expression {
         compiler: &mut Compiler,
         span: Span,
         chunk: &mut Chunk,
-        assign_value: Option<Positioned<Expression>>,
+        assignment: Option<Assignment>,
     ) -> CompileResult<()> {
         match self {
             Self::Literal(value) => value.compile(compiler, span, chunk),
+            Self::Formatted(value) => value.compile(compiler, span, chunk),
             Self::FunctionCall(value) => value.compile(compiler, span, chunk),
-            Self::Ident(value) => value.compile(compiler, span, chunk, assign_value),
+            Self::Func(value) => value.compile(compiler, span, chunk),
+            Self::Ident(value) => value.compile(compiler, span, chunk, assignment),
             Self::NewInstance(value) => value.compile(compiler, span, chunk),
             Self::IfElse(value) => value.compile(compiler, span, chunk),
             Self::Block(value) => {
@@ -31,8 +40,12 @@ impl CompileAssign for Expression {
             }
             Self::Match(value) => value.compile(compiler, span, chunk),
             Self::Binary(value) => value.compile(compiler, span, chunk),
-            Self::Index(value) => value.compile(compiler, span, chunk, assign_value),
-            _ => todo!(),
+            Self::Index(value) => value.compile(compiler, span, chunk, assignment),
+            Self::Range(value) => value.compile(compiler, span, chunk),
+            Self::Unary(value) => value.compile(compiler, span, chunk),
+            Self::Cast(value) => value.compile(compiler, span, chunk),
+            Self::Array(value) => value.compile(compiler, span, chunk),
+            Self::Object(value) => value.compile(compiler, span, chunk),
         }
     }
 }
@@ -41,15 +54,23 @@ impl GetType for Expression {
     fn get_type(&self, compiler: &Compiler, span: Span) -> CompileResult<Type> {
         match self {
             Self::Literal(value) => value.get_type(compiler, span),
+            Self::Formatted(value) => value.get_type(compiler, span),
             Self::FunctionCall(value) => value.get_type(compiler, span), // no functions
+            Self::Func(value) => value.get_type(compiler, span),
             Self::Ident(value) => value.get_type(compiler, span),
-            Self::Object(_) => todo!(), // no objects
-            Self::Array(_) => todo!(),  // no arrays
+            Self::Object(value) => value.get_type(compiler, span),
+            Self::Array(value) => value.get_type(compiler, span),
             Self::IfElse(value) => value.get_type(compiler, span),
             Self::Block(value) => value.get_type(compiler, span),
             Self::Match(value) => value.get_type(compiler, span),
             Self::Binary(value) => value.get_type(compiler, span),
             Self::Index(value) => value.get_type(compiler, span),
+            Self::Range(value) => value.get_type(compiler, span),
+            Self::Unary(value) => value.get_type(compiler, span),
+            Self::Cast(value) => value.get_type(compiler, span),
+            Self::NewInstance(value) if value.variant.is_some() => {
+                compiler.resolve_type(Type::Named(value.target.to_string()), value.target.span)
+            }
             Self::NewInstance(value) => value.target.get_type(compiler, span), // no objects/arrays
         }
     }
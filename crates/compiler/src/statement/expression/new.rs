@@ -1,10 +1,137 @@
 use crate::{CompileAssign, GetType, InstanceArgsType, prelude::*};
 
+/// Compiles `new Enum.Variant(...)` and `new Enum.Variant { ... }`.
+fn compile_variant(
+    compiler: &mut Compiler,
+    chunk: &mut Chunk,
+    enumeration: EnumType,
+    variant: Positioned<Ident>,
+    args: Positioned<InstanceArgs>,
+) -> CompileResult<()> {
+    let Some(tag) = enumeration
+        .variants
+        .iter()
+        .position(|(name, _)| name == &*variant.value)
+    else {
+        return Err(CompileError::PropertyNotExist {
+            target: enumeration.name,
+            property: variant.value.0,
+            defined_at: None,
+            at: variant.span,
+        });
+    };
+
+    let (name, fields) = &enumeration.variants[tag];
+
+    let values = match (fields, args.value) {
+        (VariantFields::Unit | VariantFields::Tuple(_), InstanceArgs::Record(values)) => values,
+        (VariantFields::Struct(fields), InstanceArgs::Struct(mut values)) => {
+            for value in &values {
+                if !fields
+                    .iter()
+                    .any(|(name, _)| name == &*value.value.name.value)
+                {
+                    return Err(CompileError::PropertyNotExist {
+                        target: format!("{}.{name}", enumeration.name),
+                        property: value.value.name.value.0.clone(),
+                        defined_at: None,
+                        at: value.span,
+                    });
+                }
+            }
+
+            values.sort_by_key(|value| {
+                fields
+                    .iter()
+                    .position(|(name, _)| name == &*value.value.name.value)
+            });
+
+            values.into_iter().map(|value| value.value.value).collect()
+        }
+        (VariantFields::Struct(_), InstanceArgs::Record(_)) => {
+            return Err(CompileError::InvalidInstanceArgs {
+                expected: InstanceArgsType::Struct,
+                got: InstanceArgsType::Record,
+                instance_at: None,
+                at: args.span,
+            });
+        }
+        (_, InstanceArgs::Struct(_)) => {
+            return Err(CompileError::InvalidInstanceArgs {
+                expected: InstanceArgsType::Record,
+                got: InstanceArgsType::Struct,
+                instance_at: None,
+                at: args.span,
+            });
+        }
+    };
+
+    let types = fields.types();
+
+    if values.len() != types.len() {
+        return Err(CompileError::InvalidArgumentsCount {
+            expected: types.len(),
+            got: values.len(),
+            function_at: None,
+            at: args.span,
+        });
+    }
+
+    for (value, ty) in values.iter().zip(&types) {
+        let found = value.get_type(compiler, value.span)?;
+
+        if !ty.compare(&found) {
+            return Err(CompileError::TypeExpected {
+                expected: ty.clone(),
+                found,
+                at: value.span,
+            });
+        }
+    }
+
+    for value in values {
+        let (span, value) = value.unpack();
+
+        value.compile(compiler, span, chunk, None)?;
+    }
+
+    let template = Compiler::create_const(
+        chunk,
+        Value::object(Object::Variant(Variant {
+            enum_name: enumeration.name.clone(),
+            name: name.clone(),
+            tag,
+            fields: vec![Value::None; types.len()],
+        })),
+    );
+
+    chunk.push(variant.span.line, OpCode::CreateVariant(template));
+
+    Ok(())
+}
+
 impl Compile for NewInstanceExpression {
+    #[allow(clippy::too_many_lines)]
     fn compile(self, compiler: &mut Compiler, span: Span, chunk: &mut Chunk) -> CompileResult<()> {
-        let (slot, variable) = compiler.get_var(self.target.span, &self.target.value.0)?;
+        if let Some(variant) = self.variant {
+            let ty =
+                compiler.resolve_type(Type::Named(self.target.to_string()), self.target.span)?;
+
+            let Type::Enum(enumeration) = ty else {
+                return Err(CompileError::PropertyNotExist {
+                    target: ty.to_string(),
+                    property: variant.value.0,
+                    defined_at: None,
+                    at: variant.span,
+                });
+            };
 
-        let depth = variable.depth;
+            return compile_variant(compiler, chunk, enumeration, variant, self.args);
+        }
+
+        let variable = compiler
+            .get_var(self.target.span, &self.target.value.0)?
+            .clone();
 
         if let Type::Record(RecordType { fields, .. }) = &variable.ty {
             if let InstanceArgs::Record(values) = self.args.value {
@@ -20,7 +147,7 @@ impl Compile for NewInstanceExpression {
                 for (value, field) in values.iter().zip(fields) {
                     let ty = value.get_type(compiler, span)?;
 
-                    if !ty.compare(field) {
+                    if !field.compare(&ty) {
                         return Err(CompileError::TypeExpected {
                             expected: field.clone(),
                             found: ty,
@@ -35,10 +162,7 @@ impl Compile for NewInstanceExpression {
                     value.compile(compiler, span, chunk, None)?;
                 }
 
-                chunk.push(
-                    span.line,
-                    OpCode::GetLocal(depth as isize - compiler.scope_depth as isize, slot),
-                );
+                compiler.load_var(chunk, self.target.span, &self.target.value.0)?;
 
                 chunk.push(span.line, OpCode::CreateInstance);
 
@@ -54,11 +178,10 @@ impl Compile for NewInstanceExpression {
         } else if let Type::Struct(structure) = &variable.ty {
             if let InstanceArgs::Struct(mut values) = self.args.value {
                 for value in &values {
-                    if structure
+                    if !structure
                         .fields
                         .iter()
-                        .position(|field| field.0 == *value.value.name.value)
-                        .is_none()
+                        .any(|field| field.0 == *value.value.name.value)
                     {
                         return Err(CompileError::PropertyNotExist {
                             target: structure.name.clone(),
@@ -79,7 +202,7 @@ impl Compile for NewInstanceExpression {
                 for (value, (_, field)) in values.iter().zip(&structure.fields) {
                     let ty = value.value.value.get_type(compiler, span)?;
 
-                    if !ty.compare(field) {
+                    if !field.compare(&ty) {
                         return Err(CompileError::TypeExpected {
                             expected: field.clone(),
                             found: ty,
@@ -94,10 +217,7 @@ impl Compile for NewInstanceExpression {
                     value.compile(compiler, span, chunk, None)?;
                 }
 
-                chunk.push(
-                    span.line,
-                    OpCode::GetLocal(depth as isize - compiler.scope_depth as isize, slot),
-                );
+                compiler.load_var(chunk, self.target.span, &self.target.value.0)?;
 
                 chunk.push(span.line, OpCode::CreateInstance);
 
@@ -122,7 +242,7 @@ impl Compile for NewInstanceExpression {
                         fields: Vec::new(),
                     }),
                 ],
-                found: variable.ty.clone(),
+                found: variable.ty,
                 at: self.target.span,
             })
         }
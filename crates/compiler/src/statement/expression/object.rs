@@ -0,0 +1,49 @@
+use crate::{CompileAssign, GetType, prelude::*};
+
+impl Compile for ObjectExpr {
+    fn compile(
+        mut self,
+        compiler: &mut Compiler,
+        span: Span,
+        chunk: &mut Chunk,
+    ) -> CompileResult<()> {
+        let mut fields = Vec::new();
+
+        // fields are laid out in the order of `ObjectType`, so they are evaluated sorted by name
+        self.properties
+            .sort_by(|a, b| a.value.name.value.0.cmp(&b.value.name.value.0));
+
+        for property in self.properties {
+            let ObjectProperty { name, value } = property.value;
+
+            let (span, value) = value.unpack();
+
+            value.compile(compiler, span, chunk, None)?;
+
+            fields.push((name.value.0, Value::None));
+        }
+
+        let template = Compiler::create_const(chunk, Value::object(Object::Object(fields)));
+
+        chunk.push(span.line, OpCode::CreateObject(template));
+
+        Ok(())
+    }
+}
+
+impl GetType for ObjectExpr {
+    fn get_type(&self, compiler: &Compiler, span: Span) -> CompileResult<Type> {
+        let mut fields = Vec::new();
+
+        for property in &self.properties {
+            fields.push((
+                property.value.name.value.to_string(),
+                property.value.value.get_type(compiler, span)?,
+            ));
+        }
+
+        fields.sort_by(|(name, _), (other, _)| name.cmp(other));
+
+        Ok(Type::Object(ObjectType { fields }))
+    }
+}
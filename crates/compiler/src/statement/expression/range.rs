@@ -0,0 +1,31 @@
+use crate::{CompileAssign, GetType, prelude::*};
+
+impl Compile for RangeExpression {
+    fn compile(self, compiler: &mut Compiler, span: Span, chunk: &mut Chunk) -> CompileResult<()> {
+        for bound in [self.start, self.end] {
+            let ty = bound.get_type(compiler, span)?;
+
+            if ty != Type::Integer {
+                return Err(CompileError::TypeExpected {
+                    expected: Type::Integer,
+                    found: ty,
+                    at: bound.span,
+                });
+            }
+
+            let (span, value) = bound.unpack();
+
+            value.compile(compiler, span, chunk, None)?;
+        }
+
+        chunk.push(span.line, OpCode::CreateRange(self.inclusive));
+
+        Ok(())
+    }
+}
+
+impl GetType for RangeExpression {
+    fn get_type(&self, _: &Compiler, _: Span) -> CompileResult<Type> {
+        Ok(Type::Range)
+    }
+}
@@ -0,0 +1,45 @@
+use crate::{CompileAssign, GetType, prelude::*};
+
+impl Compile for UnaryExpression {
+    fn compile(self, compiler: &mut Compiler, span: Span, chunk: &mut Chunk) -> CompileResult<()> {
+        self.get_type(compiler, span)?;
+
+        {
+            let (span, value) = self.value.unpack();
+
+            value.compile(compiler, span, chunk, None)?;
+        }
+
+        chunk.push(
+            self.operator.span.line,
+            match self.operator.value {
+                UnaryOperator::Not => OpCode::Not,
+                UnaryOperator::Negate => OpCode::Negate,
+            },
+        );
+
+        Ok(())
+    }
+}
+
+impl GetType for UnaryExpression {
+    fn get_type(&self, compiler: &Compiler, span: Span) -> CompileResult<Type> {
+        let ty = self.value.get_type(compiler, span)?;
+
+        match self.operator.value {
+            UnaryOperator::Not if ty != Type::Boolean => Err(CompileError::TypeExpected {
+                expected: Type::Boolean,
+                found: ty,
+                at: self.value.span,
+            }),
+            UnaryOperator::Negate if !matches!(ty, Type::Float | Type::Integer) => {
+                Err(CompileError::OneOfTypeExpected {
+                    expected: vec![Type::Float, Type::Integer],
+                    found: ty,
+                    at: self.value.span,
+                })
+            }
+            _ => Ok(ty),
+        }
+    }
+}
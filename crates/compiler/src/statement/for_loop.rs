@@ -0,0 +1,54 @@
+use crate::{CompileAssign, GetType, prelude::*};
+
+impl Compile for ForStatement {
+    fn compile(self, compiler: &mut Compiler, span: Span, chunk: &mut Chunk) -> CompileResult<()> {
+        let target_type = self.target.get_type(compiler, span)?;
+
+        let Some(item_type) = target_type.iterator_item() else {
+            return Err(CompileError::NotIterable {
+                found: target_type,
+                at: self.target.span,
+            });
+        };
+
+        {
+            let (span, value) = self.target.unpack();
+
+            value.compile(compiler, span, chunk, None)?;
+        }
+
+        chunk.push(span.line, OpCode::GetIter);
+
+        let start = chunk.len();
+        let mut next = start;
+
+        compiler.push_loop(start);
+
+        compiler.compile_loop_body(chunk, self.body, |compiler, chunk| {
+            next = chunk.len();
+
+            chunk.push(span.line, OpCode::IterNext(0));
+
+            let slot =
+                compiler.create_var(self.name.value.0, false, item_type, Some(self.name.span));
+
+            compiler.store_slot(chunk, span.line, slot);
+
+            Ok(())
+        })?;
+
+        Compiler::jump_back(chunk, span.line, start);
+
+        // iterator is exhausted, frame of the body is still there
+        Compiler::patch_jump(chunk, next, chunk.len() - 1);
+
+        chunk.push(span.line, OpCode::PopFrame);
+
+        // `break` already popped the frame
+        compiler.pop_loop(chunk);
+
+        chunk.push(span.line, OpCode::Pop);
+
+        Ok(())
+    }
+}
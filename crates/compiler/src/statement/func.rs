@@ -1,82 +1,144 @@
-use crate::{prelude::*, CompilePositioned, GetType};
+use crate::{CompilePositioned, GetType, prelude::*, statement::block_returns};
+
+/// Resolves types of function arguments and of its output.
+///
+/// # Errors
+///
+/// Returns `CompileError::UnknownType` if any of the types is not declared
+pub fn signature(
+    compiler: &Compiler,
+    args: &[Positioned<FuncArg>],
+    output_type: Option<&Positioned<Type>>,
+) -> CompileResult<(Vec<Type>, Type)> {
+    let args = args
+        .iter()
+        .map(|arg| compiler.resolve_type(arg.value.ty.value.clone(), arg.value.ty.span))
+        .collect::<CompileResult<_>>()?;
+
+    let output = match output_type {
+        Some(ty) => compiler.resolve_type(ty.value.clone(), ty.span)?,
+        None => Type::None,
+    };
+
+    Ok((args, output))
+}
 
-impl Compile for FuncStatement {
-    fn compile(
-        self,
-        compiler: &mut Compiler,
-        span: Span,
-        chunk: &mut Chunk,
-    ) -> crate::CompileResult<()> {
-        let mut alt_compiler = Compiler::new();
+/// Compiles function and leaves closure of it on the stack, returns type of the function.
+///
+/// Body sees the function itself as variable `name`, so it can be called recursively.
+///
+/// # Errors
+///
+/// Returns compile error if signature or body fails to compile
+pub fn compile_function(
+    compiler: &mut Compiler,
+    chunk: &mut Chunk,
+    name: String,
+    args: Positioned<Vec<Positioned<FuncArg>>>,
+    output_type: Option<&Positioned<Type>>,
+    body: Positioned<Block>,
+    span: Span,
+) -> CompileResult<Type> {
+    let (arg_types, output) = signature(compiler, &args.value, output_type)?;
+    let output_span = output_type.map(|value| value.span);
+
+    let ty = Type::Function(FunctionType {
+        args: arg_types.clone(),
+        output_type: Box::new(output.clone()),
+    });
+
+    let returns = block_returns(&body.value);
+    let body_span = body.span;
+
+    let ((function_chunk, body_output_type), captures) = compiler.nested(|compiler| {
         let mut function_chunk = Chunk::new();
 
-        alt_compiler.push_scope();
+        compiler.output = Some(output.clone());
+        compiler.push_scope();
 
-        let mut args = Vec::new();
-
-        for arg in self.args.value {
-            args.push(arg.value.ty.value.clone());
-
-            alt_compiler.create_var(
-                arg.value.name.value.0,
-                true,
-                arg.value.ty.value,
-                Some(arg.span),
-            );
+        for (arg, ty) in args.value.into_iter().zip(&arg_types) {
+            compiler.create_var(arg.value.name.value.0, true, ty.clone(), Some(arg.span));
         }
 
-        let output_span = self.output_type.as_ref().map(|value| value.span);
-        let output = self.output_type.map_or(Type::None, |value| value.value);
-
-        let ty = Type::Function(FunctionType {
-            args: args.clone(),
-            output_type: Box::new(output.clone()),
-        });
+        compiler.create_var(name.clone(), false, ty.clone(), Some(span));
 
-        let variable = compiler.create_var(self.name.to_string(), false, ty.clone(), Some(span));
-
-        alt_compiler.create_var(self.name.to_string(), false, ty, Some(span));
-
-        for value in self.body.value.statements {
-            value.compile(&mut alt_compiler, &mut function_chunk)?;
+        for value in body.value.statements {
+            value.compile(compiler, &mut function_chunk)?;
         }
 
         let mut body_output_type = Type::None;
 
-        if let Some(statement) = self.body.value.return_statement {
-            body_output_type = statement.get_type(&alt_compiler, span)?;
+        if let Some(statement) = body.value.return_statement {
+            body_output_type = statement.get_type(compiler, span)?;
 
-            statement.compile(&mut alt_compiler, &mut function_chunk)?;
+            statement.compile(compiler, &mut function_chunk)?;
 
-            function_chunk.push(self.body.span.line, OpCode::Return);
+            function_chunk.push(body.span.line, OpCode::Return);
         }
 
-        alt_compiler.pop_scope(self.body.span.line, &mut function_chunk);
+        compiler.pop_scope(body.span.line, &mut function_chunk);
 
         function_chunk.push(0, OpCode::Halt);
 
-        if body_output_type != output {
-            return Err(CompileError::TypeExpected {
+        Ok((function_chunk, body_output_type))
+    })?;
+
+    // every path ends with `return`, so the value of the body is never used
+    if !returns && !output.compare(&body_output_type) {
+        if body_output_type == Type::None {
+            return Err(CompileError::MissingReturn {
                 expected: output,
-                found: body_output_type,
-                at: span,
+                at: body_span,
             });
         }
 
-        let constant = Compiler::create_const(
+        return Err(CompileError::TypeExpected {
+            expected: output,
+            found: body_output_type,
+            at: output_span.unwrap_or(span),
+        });
+    }
+
+    let constant = Compiler::create_const(
+        chunk,
+        Value::object(Object::Function(Function {
+            meta: FunctionMetadata {
+                name,
+                args: arg_types,
+                output,
+            },
+            chunk: function_chunk,
+            captures,
+        })),
+    );
+
+    chunk.push(span.line, OpCode::Closure(constant));
+
+    Ok(ty)
+}
+
+impl Compile for FuncStatement {
+    fn compile(
+        self,
+        compiler: &mut Compiler,
+        span: Span,
+        chunk: &mut Chunk,
+    ) -> crate::CompileResult<()> {
+        let name = self.name.to_string();
+
+        let ty = compile_function(
+            compiler,
             chunk,
-            Value::object(Object::Function(Function {
-                meta: FunctionMetadata {
-                    name: self.name.value.0,
-                    args,
-                    output,
-                },
-                chunk: function_chunk,
-            })),
-        );
-
-        chunk.push(span.line, OpCode::LoadConst(constant));
-        chunk.push(span.line, OpCode::SetLocal(None, variable));
+            name.clone(),
+            self.args,
+            self.output_type.as_ref(),
+            self.body,
+            span,
+        )?;
+
+        let variable = compiler.get_or_create_var(name, ty, false, Some(span));
+
+        compiler.store_slot(chunk, span.line, variable);
 
         Ok(())
     }
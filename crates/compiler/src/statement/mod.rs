@@ -1,10 +1,25 @@
+mod enumeration;
 mod expression;
+mod for_loop;
 mod func;
+mod pattern;
 mod record;
 mod structure;
 mod variable;
+mod while_loop;
 
-use crate::{prelude::*, CompileAssign, GetType};
+use crate::{
+    CompileAssign, GetType,
+    prelude::*,
+    refers_to,
+    statement::{
+        enumeration::enum_type,
+        func::signature,
+        pattern::{is_irrefutable, pattern_names},
+        record::record_type,
+        structure::struct_type,
+    },
+};
 
 impl Compile for Statement {
     fn compile(self, compiler: &mut Compiler, span: Span, chunk: &mut Chunk) -> CompileResult<()> {
@@ -12,9 +27,14 @@ impl Compile for Statement {
             Self::Variable(value) => value.compile(compiler, span, chunk),
             Self::Struct(value) => value.compile(compiler, span, chunk),
             Self::Record(value) => value.compile(compiler, span, chunk),
+            Self::Enum(value) => value.compile(compiler, span, chunk),
             Self::Func(value) => value.compile(compiler, span, chunk),
+            Self::ForIn(value) => value.compile(compiler, span, chunk),
+            Self::WhileLoop(value) => value.compile(compiler, span, chunk),
+            Self::Break => compiler.compile_break(span, chunk),
+            Self::Continue => compiler.compile_continue(span, chunk),
+            Self::Return(value) => compiler.compile_return(value, span, chunk),
             Self::Expression(value) => value.compile(compiler, span, chunk, None),
-            _ => todo!(),
         }
     }
 }
@@ -26,4 +46,280 @@ impl GetType for Statement {
             _ => Ok(Type::None),
         }
     }
-}
\ No newline at end of file
+}
+
+/// Checks whether `statement` declares type or function, which can be used before it.
+pub const fn is_declaration(statement: &Statement) -> bool {
+    matches!(
+        statement,
+        Statement::Struct(_) | Statement::Record(_) | Statement::Enum(_) | Statement::Func(_)
+    )
+}
+
+/// Resolves name and type declared by `statement`, if it declares one.
+fn declared_type(
+    compiler: &Compiler,
+    statement: &Statement,
+) -> Option<(String, CompileResult<Type>)> {
+    Some(match statement {
+        Statement::Struct(value) => (
+            value.name.to_string(),
+            struct_type(compiler, value).map(Type::Struct),
+        ),
+        Statement::Record(value) => (
+            value.name.to_string(),
+            record_type(compiler, value).map(Type::Record),
+        ),
+        Statement::Enum(value) => (
+            value.name.to_string(),
+            enum_type(compiler, value).map(Type::Enum),
+        ),
+        _ => return None,
+    })
+}
+
+/// Returns name of function or type declared by `statement`.
+const fn declared_name(statement: &Statement) -> Option<&Positioned<Ident>> {
+    match statement {
+        Statement::Struct(value) => Some(&value.name),
+        Statement::Record(value) => Some(&value.name),
+        Statement::Enum(value) => Some(&value.name),
+        Statement::Func(value) => Some(&value.name),
+        _ => None,
+    }
+}
+
+/// Checks that functions and types of the top level don't share names with each other or with
+/// its variables, hoisted declaration would be mixed up with them otherwise.
+fn check_names(statements: &[Positioned<Statement>]) -> CompileResult<()> {
+    // name, where it's declared and whether it's declared by function or type
+    let mut names: Vec<(String, Span, bool)> = Vec::new();
+
+    for statement in statements {
+        let (found, declaration) = match &statement.value {
+            Statement::Variable(value) => (pattern_names(&value.pattern), false),
+            statement => match declared_name(statement) {
+                Some(name) => (vec![(name.value.to_string(), name.span)], true),
+                None => continue,
+            },
+        };
+
+        for (name, at) in found {
+            // variables may be declared again
+            if let Some((_, declared_at, _)) = names.iter().find(|(other, _, other_declaration)| {
+                other == &name && (declaration || *other_declaration)
+            }) {
+                return Err(CompileError::AlreadyDeclared {
+                    name,
+                    declared_at: *declared_at,
+                    at,
+                });
+            }
+
+            names.push((name, at, declaration));
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns unresolved types of the fields of type declared by `statement`.
+fn field_types(statement: &Statement) -> Vec<&Positioned<Type>> {
+    match statement {
+        Statement::Struct(value) => value
+            .fields
+            .value
+            .iter()
+            .map(|field| &field.value.ty)
+            .collect(),
+        Statement::Record(value) => value.fields.value.iter().collect(),
+        Statement::Enum(value) => value
+            .variants
+            .value
+            .iter()
+            .flat_map(|variant| match &variant.value.fields {
+                EnumVariantFields::Unit => Vec::new(),
+                EnumVariantFields::Tuple(fields) => fields.iter().collect(),
+                EnumVariantFields::Struct(fields) => {
+                    fields.iter().map(|field| &field.value.ty).collect()
+                }
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Returns fields leading from type `from` to type `to`, each type of `graph` has the types it
+/// refers to along with the referring field.
+fn path_to(
+    graph: &[(String, Vec<(usize, Span)>)],
+    from: usize,
+    to: usize,
+    visited: &mut [bool],
+) -> Option<Vec<(usize, Span)>> {
+    for &(next, span) in &graph[from].1 {
+        if next == to {
+            return Some(vec![(from, span)]);
+        }
+
+        if !visited[next] {
+            visited[next] = true;
+
+            if let Some(mut path) = path_to(graph, next, to, visited) {
+                path.insert(0, (from, span));
+
+                return Some(path);
+            }
+        }
+    }
+
+    None
+}
+
+/// Finds types among the ones which couldn't be resolved that contain each other.
+fn recursive_types(types: &[&Positioned<Statement>]) -> Option<CompileError> {
+    let names = types
+        .iter()
+        .filter_map(|statement| declared_name(&statement.value))
+        .map(|name| name.value.to_string())
+        .collect::<Vec<_>>();
+
+    let graph = types
+        .iter()
+        .zip(&names)
+        .map(|(statement, name)| {
+            let references = field_types(&statement.value)
+                .into_iter()
+                .flat_map(|ty| {
+                    names
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, name)| refers_to(&ty.value, name))
+                        .map(|(index, _)| (index, ty.span))
+                })
+                .collect();
+
+            (name.clone(), references)
+        })
+        .collect::<Vec<_>>();
+
+    (0..graph.len()).find_map(|start| {
+        let path = path_to(&graph, start, start, &mut vec![false; graph.len()])?;
+
+        Some(CompileError::RecursiveType {
+            name: graph[start].0.clone(),
+            through: path[1..]
+                .iter()
+                .map(|(index, _)| graph[*index].0.clone())
+                .collect(),
+            at: path[0].1,
+        })
+    })
+}
+
+/// Declares types and functions of the top level before compiling it, so they can be used
+/// regardless of the order they are declared in.
+///
+/// Invalid declarations are reported when the declarations themselves are compiled.
+///
+/// # Errors
+///
+/// Returns `CompileError::AlreadyDeclared` if function or type has the same name as another
+/// declaration or variable of the top level, or `CompileError::RecursiveType` if types contain
+/// each other
+pub fn hoist(compiler: &mut Compiler, statements: &[Positioned<Statement>]) -> CompileResult<()> {
+    check_names(statements)?;
+
+    let mut types = statements
+        .iter()
+        .filter(|statement| {
+            matches!(
+                statement.value,
+                Statement::Struct(_) | Statement::Record(_) | Statement::Enum(_)
+            )
+        })
+        .collect::<Vec<_>>();
+
+    // types may refer to the ones declared after them, so they are resolved until no more of
+    // them can be
+    loop {
+        let count = types.len();
+
+        types.retain(|statement| {
+            let Some((name, Ok(ty))) = declared_type(compiler, &statement.value) else {
+                return true;
+            };
+
+            compiler.types.insert(name.clone(), ty.clone());
+            compiler.get_or_create_var(name, ty, false, Some(statement.span));
+
+            false
+        });
+
+        if types.len() == count {
+            break;
+        }
+    }
+
+    // types containing each other are never resolved, as each of them waits for another one
+    if let Some(error) = recursive_types(&types) {
+        return Err(error);
+    }
+
+    for statement in statements {
+        if let Statement::Func(func) = &statement.value
+            && let Ok((args, output)) =
+                signature(compiler, &func.args.value, func.output_type.as_ref())
+        {
+            compiler.get_or_create_var(
+                func.name.to_string(),
+                Type::Function(FunctionType {
+                    args,
+                    output_type: Box::new(output),
+                }),
+                false,
+                Some(statement.span),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that `statement` exits the function with `return` whichever path is taken.
+pub fn statement_returns(statement: &Statement) -> bool {
+    match statement {
+        Statement::Return(_) => true,
+        Statement::Expression(expression) => expression_returns(expression),
+        _ => false,
+    }
+}
+
+/// Checks that `expression` exits the function with `return` whichever path is taken.
+pub fn expression_returns(expression: &Expression) -> bool {
+    match expression {
+        Expression::Block(block) => block_returns(block),
+        Expression::IfElse(value) => value.else_block.as_ref().is_some_and(|else_block| {
+            block_returns(&value.block.value) && expression_returns(&else_block.value)
+        }),
+        // match without catch-all arm is not known to be exhaustive here
+        Expression::Match(value) => {
+            value.variants.iter().any(|variant| {
+                variant.value.guard.is_none() && is_irrefutable(&variant.value.case.value)
+            }) && value
+                .variants
+                .iter()
+                .all(|variant| expression_returns(&variant.value.then.value))
+        }
+        _ => false,
+    }
+}
+
+/// Checks that `block` exits the function with `return` whichever path is taken.
+pub fn block_returns(block: &Block) -> bool {
+    block
+        .statements
+        .iter()
+        .chain(block.return_statement.as_deref())
+        .any(|statement| statement_returns(&statement.value))
+}
@@ -0,0 +1,393 @@
+use crate::{CompileAssign, GetType, InstanceArgsType, prelude::*};
+
+/// Location of the value checked by pattern: variable and path of fields inside of it.
+#[derive(Clone)]
+pub struct Place {
+    slot: usize,
+    fields: Vec<usize>,
+}
+
+impl Place {
+    pub const fn variable(slot: usize) -> Self {
+        Self {
+            slot,
+            fields: Vec::new(),
+        }
+    }
+
+    fn field(&self, index: usize) -> Self {
+        let mut place = self.clone();
+
+        place.fields.push(index);
+
+        place
+    }
+
+    fn load(&self, compiler: &Compiler, chunk: &mut Chunk, line: usize) {
+        compiler.load_slot(chunk, line, self.slot);
+
+        for field in &self.fields {
+            chunk.push(line, OpCode::GetProperty(*field));
+        }
+    }
+}
+
+/// Field of destructured instance: its index, pattern and type.
+type Field<'a> = (usize, &'a Positioned<Pattern>, Type);
+
+/// Resolves type named by instance `pattern` and checks it against value of type `ty`.
+///
+/// Returns tag of enum variant, if pattern matches one, and fields of the instance.
+fn instance_type(
+    compiler: &Compiler,
+    ty: &Type,
+    pattern: &InstancePattern,
+    at: Span,
+) -> CompileResult<(Option<usize>, VariantFields)> {
+    let declared =
+        compiler.resolve_type(Type::Named(pattern.target.to_string()), pattern.target.span)?;
+
+    Ok(match (declared, &pattern.variant) {
+        (Type::Enum(enumeration), Some(variant)) => {
+            let Some(tag) = enumeration
+                .variants
+                .iter()
+                .position(|(name, _)| name == &*variant.value)
+            else {
+                return Err(CompileError::PropertyNotExist {
+                    target: enumeration.name,
+                    property: variant.to_string(),
+                    defined_at: None,
+                    at: variant.span,
+                });
+            };
+
+            if !matches!(ty, Type::Enum(target) if target.name == enumeration.name) {
+                return Err(CompileError::TypeExpected {
+                    expected: ty.clone(),
+                    found: Type::Enum(enumeration),
+                    at,
+                });
+            }
+
+            (Some(tag), enumeration.variants[tag].1.clone())
+        }
+        (declared @ (Type::Record(_) | Type::Struct(_)), None) => {
+            if &declared != ty {
+                return Err(CompileError::TypeExpected {
+                    expected: ty.clone(),
+                    found: declared,
+                    at,
+                });
+            }
+
+            match declared {
+                Type::Record(record) => (None, VariantFields::Tuple(record.fields)),
+                Type::Struct(structure) => (None, VariantFields::Struct(structure.fields)),
+                _ => unreachable!(),
+            }
+        }
+        (declared, Some(variant)) => {
+            return Err(CompileError::PropertyNotExist {
+                target: declared.to_string(),
+                property: variant.to_string(),
+                defined_at: None,
+                at: variant.span,
+            });
+        }
+        (declared, None) => {
+            return Err(CompileError::TypeExpected {
+                expected: ty.clone(),
+                found: declared,
+                at: pattern.target.span,
+            });
+        }
+    })
+}
+
+/// Resolves instance matched by `pattern` against value of type `ty`.
+///
+/// Returns tag of enum variant, if pattern matches one, and patterns of the fields.
+fn instance_fields<'a>(
+    compiler: &Compiler,
+    ty: &Type,
+    pattern: &'a InstancePattern,
+    at: Span,
+) -> CompileResult<(Option<usize>, Vec<Field<'a>>)> {
+    let (tag, fields) = instance_type(compiler, ty, pattern, at)?;
+
+    let fields = match (fields, &pattern.fields.value) {
+        (
+            fields @ (VariantFields::Unit | VariantFields::Tuple(_)),
+            PatternFields::Record(patterns),
+        ) => {
+            let types = fields.types();
+
+            if patterns.len() != types.len() {
+                return Err(CompileError::InvalidArgumentsCount {
+                    expected: types.len(),
+                    got: patterns.len(),
+                    function_at: None,
+                    at: pattern.fields.span,
+                });
+            }
+
+            patterns
+                .iter()
+                .zip(types)
+                .enumerate()
+                .map(|(index, (pattern, ty))| (index, pattern, ty))
+                .collect()
+        }
+        (VariantFields::Struct(fields), PatternFields::Struct(patterns)) => patterns
+            .iter()
+            .map(|field| {
+                fields
+                    .iter()
+                    .position(|(name, _)| name == &*field.value.field.value)
+                    .map(|index| (index, &field.value.pattern, fields[index].1.clone()))
+                    .ok_or_else(|| CompileError::PropertyNotExist {
+                        target: pattern.target.to_string(),
+                        property: field.value.field.to_string(),
+                        defined_at: None,
+                        at: field.span,
+                    })
+            })
+            .collect::<CompileResult<_>>()?,
+        (VariantFields::Struct(_), PatternFields::Record(_)) => {
+            return Err(CompileError::InvalidInstanceArgs {
+                expected: InstanceArgsType::Struct,
+                got: InstanceArgsType::Record,
+                instance_at: None,
+                at: pattern.fields.span,
+            });
+        }
+        (_, PatternFields::Struct(_)) => {
+            return Err(CompileError::InvalidInstanceArgs {
+                expected: InstanceArgsType::Record,
+                got: InstanceArgsType::Struct,
+                instance_at: None,
+                at: pattern.fields.span,
+            });
+        }
+    };
+
+    Ok((tag, fields))
+}
+
+/// Checks that value pattern can be compared with value of type `ty`.
+///
+/// Returns `true` if pattern is a range the value must be contained in.
+fn value_pattern(
+    compiler: &Compiler,
+    expression: &Expression,
+    ty: &Type,
+    at: Span,
+) -> CompileResult<bool> {
+    let found = expression.get_type(compiler, at)?;
+    let in_range = found == Type::Range && ty == &Type::Integer;
+
+    // optional value can be compared with `none` and values of the wrapped type
+    if !in_range && !ty.compare(&found) {
+        return Err(CompileError::TypeExpected {
+            expected: ty.clone(),
+            found,
+            at,
+        });
+    }
+
+    Ok(in_range)
+}
+
+/// Checks that every field of the instance pattern matches any value.
+pub fn fields_irrefutable(fields: &PatternFields) -> bool {
+    match fields {
+        PatternFields::Record(patterns) => patterns
+            .iter()
+            .all(|pattern| is_irrefutable(&pattern.value)),
+        PatternFields::Struct(fields) => fields
+            .iter()
+            .all(|field| is_irrefutable(&field.value.pattern.value)),
+    }
+}
+
+/// Checks that `pattern` matches any value of its type.
+pub fn is_irrefutable(pattern: &Pattern) -> bool {
+    match pattern {
+        Pattern::Wildcard | Pattern::Binding(_) => true,
+        Pattern::Instance(instance) => {
+            instance.variant.is_none() && fields_irrefutable(&instance.fields.value)
+        }
+        Pattern::Or(patterns) => patterns
+            .iter()
+            .any(|pattern| is_irrefutable(&pattern.value)),
+        Pattern::Value(_) => false,
+    }
+}
+
+/// Returns names of variables bound by `pattern` along with their positions.
+pub fn pattern_names(pattern: &Positioned<Pattern>) -> Vec<(String, Span)> {
+    match &pattern.value {
+        Pattern::Binding(name) => vec![(name.to_string(), pattern.span)],
+        Pattern::Instance(instance) => match &instance.fields.value {
+            PatternFields::Record(patterns) => patterns.iter().flat_map(pattern_names).collect(),
+            PatternFields::Struct(fields) => fields
+                .iter()
+                .flat_map(|field| pattern_names(&field.value.pattern))
+                .collect(),
+        },
+        // every alternative binds the same variables
+        Pattern::Or(patterns) => patterns.first().map(pattern_names).unwrap_or_default(),
+        Pattern::Wildcard | Pattern::Value(_) => Vec::new(),
+    }
+}
+
+/// Returns variables bound by `pattern` when it matches value of type `ty`.
+///
+/// # Errors
+///
+/// Returns compile error if pattern can't match value of such type
+pub fn pattern_bindings(
+    compiler: &Compiler,
+    pattern: &Positioned<Pattern>,
+    ty: &Type,
+) -> CompileResult<Vec<(String, Type)>> {
+    match &pattern.value {
+        Pattern::Wildcard => Ok(Vec::new()),
+        Pattern::Binding(name) => Ok(vec![(name.to_string(), ty.clone())]),
+        Pattern::Instance(instance) => {
+            let (_, fields) = instance_fields(compiler, ty, instance, pattern.span)?;
+
+            let mut bindings = Vec::new();
+
+            for (_, pattern, ty) in fields {
+                bindings.extend(pattern_bindings(compiler, pattern, &ty)?);
+            }
+
+            Ok(bindings)
+        }
+        Pattern::Or(patterns) => {
+            let bindings = pattern_bindings(compiler, &patterns[0], ty)?;
+
+            for pattern in &patterns[1..] {
+                let other = pattern_bindings(compiler, pattern, ty)?;
+
+                // body of the arm must see the same variables whichever alternative matched
+                if let Some((name, _)) = bindings
+                    .iter()
+                    .chain(&other)
+                    .find(|binding| !bindings.contains(binding) || !other.contains(binding))
+                {
+                    return Err(CompileError::InconsistentBinding {
+                        name: name.clone(),
+                        at: pattern.span,
+                    });
+                }
+            }
+
+            Ok(bindings)
+        }
+        Pattern::Value(expression) => {
+            value_pattern(compiler, expression, ty, pattern.span)?;
+
+            Ok(Vec::new())
+        }
+    }
+}
+
+/// Compiles checks of `pattern` against the value at `place` and stores bound values into
+/// variables, which must be already declared.
+///
+/// Jumps taken when the value doesn't match are pushed to `fails`.
+///
+/// # Errors
+///
+/// Returns compile error if pattern can't match value of such type
+pub fn compile_pattern(
+    compiler: &mut Compiler,
+    chunk: &mut Chunk,
+    pattern: &Positioned<Pattern>,
+    ty: &Type,
+    place: &Place,
+    fails: &mut Vec<usize>,
+) -> CompileResult<()> {
+    let line = pattern.span.line;
+
+    match &pattern.value {
+        Pattern::Wildcard => {}
+        Pattern::Binding(name) => {
+            place.load(compiler, chunk, line);
+
+            compiler.store_var(chunk, pattern.span, &name.0)?;
+        }
+        Pattern::Instance(instance) => {
+            let (tag, fields) = instance_fields(compiler, ty, instance, pattern.span)?;
+
+            if let Some(tag) = tag {
+                place.load(compiler, chunk, line);
+
+                chunk.push(line, OpCode::IsVariant(tag));
+
+                fails.push(chunk.len());
+
+                chunk.push(line, OpCode::JumpIfFalse(0));
+            }
+
+            for (index, pattern, ty) in fields {
+                compile_pattern(compiler, chunk, pattern, &ty, &place.field(index), fails)?;
+            }
+        }
+        Pattern::Or(patterns) => {
+            let (last, patterns) = patterns.split_last().unwrap_or_else(|| unreachable!());
+            let mut matched = Vec::new();
+
+            for pattern in patterns {
+                let mut next = Vec::new();
+
+                compile_pattern(compiler, chunk, pattern, ty, place, &mut next)?;
+
+                matched.push(chunk.len());
+
+                chunk.push(line, OpCode::Jump(0));
+
+                let end = chunk.len() - 1;
+
+                for jump in next {
+                    Compiler::patch_jump(chunk, jump, end);
+                }
+            }
+
+            compile_pattern(compiler, chunk, last, ty, place, fails)?;
+
+            let end = chunk.len() - 1;
+
+            for jump in matched {
+                Compiler::patch_jump(chunk, jump, end);
+            }
+        }
+        Pattern::Value(expression) => {
+            let in_range = value_pattern(compiler, expression, ty, pattern.span)?;
+
+            place.load(compiler, chunk, line);
+
+            expression
+                .clone()
+                .compile(compiler, pattern.span, chunk, None)?;
+
+            chunk.push(
+                line,
+                if in_range {
+                    OpCode::Contains
+                } else {
+                    OpCode::Equal
+                },
+            );
+
+            fails.push(chunk.len());
+
+            chunk.push(line, OpCode::JumpIfFalse(0));
+        }
+    }
+
+    Ok(())
+}
@@ -1,5 +1,28 @@
 use crate::prelude::*;
 
+/// Resolves type declared by `statement`.
+///
+/// # Errors
+///
+/// Returns `CompileError::UnknownType` if type of any field is not declared
+pub fn record_type(compiler: &Compiler, statement: &RecordStatement) -> CompileResult<RecordType> {
+    Ok(RecordType {
+        name: statement.name.to_string(),
+        fields: statement
+            .fields
+            .value
+            .iter()
+            .map(|field| {
+                compiler.resolve_field_type(
+                    &statement.name.value.0,
+                    field.value.clone(),
+                    field.span,
+                )
+            })
+            .collect::<CompileResult<_>>()?,
+    })
+}
+
 impl Compile for RecordStatement {
     fn compile(
         self,
@@ -7,22 +30,13 @@ impl Compile for RecordStatement {
         span: Span,
         chunk: &mut Chunk,
     ) -> crate::CompileResult<()> {
-        let fields = self
-            .fields
-            .value
-            .into_iter()
-            .map(|field| field.value)
-            .collect::<Vec<_>>();
+        let record = record_type(compiler, &self)?;
+        let fields = record.fields.clone();
+        let ty = Type::Record(record);
 
-        let variable = compiler.create_var(
-            self.name.to_string(),
-            false,
-            Type::Record(RecordType {
-                name: self.name.to_string(),
-                fields: fields.clone(),
-            }),
-            Some(span),
-        );
+        compiler.types.insert(self.name.to_string(), ty.clone());
+
+        let variable = compiler.get_or_create_var(self.name.to_string(), ty, false, Some(span));
 
         let constant = Compiler::create_const(
             chunk,
@@ -33,7 +47,7 @@ impl Compile for RecordStatement {
         );
 
         chunk.push(span.line, OpCode::LoadConst(constant));
-        chunk.push(span.line, OpCode::SetLocal(None, variable));
+        compiler.store_slot(chunk, span.line, variable);
 
         Ok(())
     }
@@ -1,5 +1,34 @@
 use crate::prelude::*;
 
+/// Resolves type declared by `statement`.
+///
+/// # Errors
+///
+/// Returns `CompileError::UnknownType` if type of any field is not declared
+pub fn struct_type(compiler: &Compiler, statement: &StructStatement) -> CompileResult<StructType> {
+    let mut fields = statement
+        .fields
+        .value
+        .iter()
+        .map(|field| {
+            let StructField { name, ty } = &field.value;
+
+            Ok((
+                name.value.0.clone(),
+                compiler.resolve_field_type(&statement.name.value.0, ty.value.clone(), ty.span)?,
+            ))
+        })
+        .collect::<CompileResult<Vec<_>>>()?;
+
+    // objects with the same fields share layout with the struct
+    fields.sort_by(|(name, _), (other, _)| name.cmp(other));
+
+    Ok(StructType {
+        name: statement.name.to_string(),
+        fields,
+    })
+}
+
 impl Compile for StructStatement {
     fn compile(
         self,
@@ -7,22 +36,13 @@ impl Compile for StructStatement {
         span: Span,
         chunk: &mut Chunk,
     ) -> crate::CompileResult<()> {
-        let fields: Vec<(String, Type)> = self
-            .fields
-            .value
-            .into_iter()
-            .map(|field| (field.value.name.value.0, field.value.ty.value))
-            .collect();
-
-        let variable = compiler.create_var(
-            self.name.to_string(),
-            false,
-            Type::Struct(StructType {
-                name: self.name.to_string(),
-                fields: fields.clone(),
-            }),
-            Some(span),
-        );
+        let structure = struct_type(compiler, &self)?;
+        let fields = structure.fields.clone();
+        let ty = Type::Struct(structure);
+
+        compiler.types.insert(self.name.to_string(), ty.clone());
+
+        let variable = compiler.get_or_create_var(self.name.to_string(), ty, false, Some(span));
 
         let constant = Compiler::create_const(
             chunk,
@@ -33,7 +53,7 @@ impl Compile for StructStatement {
         );
 
         chunk.push(span.line, OpCode::LoadConst(constant));
-        chunk.push(span.line, OpCode::SetLocal(None, variable));
+        compiler.store_slot(chunk, span.line, variable);
 
         Ok(())
     }
@@ -1,4 +1,50 @@
-use crate::{CompileAssign, GetType, prelude::*};
+use crate::{
+    CompileAssign, GetType,
+    prelude::*,
+    statement::pattern::{Place, compile_pattern, is_irrefutable, pattern_bindings},
+};
+
+/// Compiles declaration with pattern on the left, `ty` is the type of the value.
+fn compile_destructuring(
+    compiler: &mut Compiler,
+    chunk: &mut Chunk,
+    pattern: &Positioned<Pattern>,
+    mutable: bool,
+    value: Positioned<Expression>,
+    ty: &Type,
+) -> CompileResult<()> {
+    if !is_irrefutable(&pattern.value) {
+        return Err(CompileError::RefutablePattern { at: pattern.span });
+    }
+
+    let bindings = pattern_bindings(compiler, pattern, ty)?;
+
+    {
+        let (span, value) = value.unpack();
+
+        value.compile(compiler, span, chunk, None)?;
+    }
+
+    // unnamed variable can't be accessed by the script, so every destructuring of the scope
+    // reuses the same one
+    let slot = compiler.get_or_create_var(String::new(), ty.clone(), false, None);
+
+    compiler.store_slot(chunk, pattern.span.line, slot);
+
+    for (name, ty) in bindings {
+        compiler.get_or_create_var(name, ty, mutable, Some(pattern.span));
+    }
+
+    // irrefutable pattern never fails, so there are no jumps to patch
+    compile_pattern(
+        compiler,
+        chunk,
+        pattern,
+        ty,
+        &Place::variable(slot),
+        &mut Vec::new(),
+    )
+}
 
 impl Compile for VariableStatement {
     fn compile(
@@ -7,18 +53,48 @@ impl Compile for VariableStatement {
         span: Span,
         chunk: &mut Chunk,
     ) -> crate::CompileResult<()> {
-        let slot = compiler.get_or_create_var(
-            self.name.to_string(),
-            self.value.get_type(compiler, span)?,
-            self.mutable.value,
-            Some(span),
-        );
+        let value_type = self.value.get_type(compiler, span)?;
 
-        let (span, value) = self.value.unpack();
+        let ty = if let Some(ty) = self.ty {
+            let ty = compiler.resolve_type(ty.value, ty.span)?;
 
-        value.compile(compiler, span, chunk, None)?;
+            if !ty.compare(&value_type) {
+                return Err(CompileError::TypeExpected {
+                    expected: ty,
+                    found: value_type,
+                    at: self.value.span,
+                });
+            }
+
+            ty
+        } else if value_type.has_unknown() {
+            return Err(CompileError::TypeAnnotationRequired {
+                at: self.value.span,
+            });
+        } else {
+            value_type
+        };
+
+        let Pattern::Binding(name) = &self.pattern.value else {
+            return compile_destructuring(
+                compiler,
+                chunk,
+                &self.pattern,
+                self.mutable.value,
+                self.value,
+                &ty,
+            );
+        };
+
+        let name = name.to_string();
+        let (value_span, value) = self.value.unpack();
+
+        // initializer is compiled first, so it sees the variable this declaration shadows
+        value.compile(compiler, value_span, chunk, None)?;
+
+        let slot = compiler.get_or_create_var(name, ty, self.mutable.value, Some(span));
 
-        chunk.push(span.line, OpCode::SetLocal(None, slot));
+        compiler.store_slot(chunk, value_span.line, slot);
 
         Ok(())
     }
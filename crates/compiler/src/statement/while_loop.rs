@@ -0,0 +1,39 @@
+use crate::{CompileAssign, GetType, prelude::*};
+
+impl Compile for WhileStatement {
+    fn compile(self, compiler: &mut Compiler, span: Span, chunk: &mut Chunk) -> CompileResult<()> {
+        let condition_type = self.condition.get_type(compiler, span)?;
+
+        if condition_type != Type::Boolean {
+            return Err(CompileError::TypeExpected {
+                expected: Type::Boolean,
+                found: condition_type,
+                at: self.condition.span,
+            });
+        }
+
+        let start = chunk.len();
+
+        {
+            let (span, value) = self.condition.unpack();
+
+            value.compile(compiler, span, chunk, None)?;
+        }
+
+        let exit = chunk.len();
+
+        chunk.push(span.line, OpCode::JumpIfFalse(0));
+
+        compiler.push_loop(start);
+
+        compiler.compile_loop_body(chunk, self.body, |_, _| Ok(()))?;
+
+        Compiler::jump_back(chunk, span.line, start);
+
+        Compiler::patch_jump(chunk, exit, chunk.len() - 1);
+
+        compiler.pop_loop(chunk);
+
+        Ok(())
+    }
+}
@@ -2,7 +2,7 @@ mod token;
 
 pub use self::token::{StringPart, Token};
 use peekmore::{PeekMore, PeekMoreIterator};
-use std::{iter, mem, ops::Neg, str::Chars};
+use std::{iter, mem, str::Chars};
 use tapt_shared::{Positioned, Span};
 
 pub struct Lexer;
@@ -15,6 +15,7 @@ impl Lexer {
             "record" => Token::Record,
             "new" => Token::New,
             "struct" => Token::Struct,
+            "enum" => Token::Enum,
             "match" => Token::Match,
             "func" => Token::Func,
             "const" => Token::Const,
@@ -22,12 +23,18 @@ impl Lexer {
             "while" => Token::While,
             "for" => Token::For,
             "in" => Token::In,
+            "break" => Token::Break,
+            "continue" => Token::Continue,
+            "return" => Token::Return,
+            "as" => Token::As,
+            "none" => Token::None,
             "if" => Token::If,
             "else" => Token::Else,
             _ => Token::Ident(ident),
         }
     }
 
+    #[allow(clippy::too_many_lines)]
     fn parse_other(chars: &mut PeekMoreIterator<Chars>, span: &mut Span, character: char) -> Token {
         match character {
             '[' => Token::BracketOpen,
@@ -63,32 +70,113 @@ impl Lexer {
             }
             ':' => Token::Colon,
             ';' => Token::Semi,
+            '+' | '-' | '*' | '/' if chars.next_if_eq(&'=').is_some() => {
+                span.end += 1;
+                span.column += 1;
+
+                match character {
+                    '+' => Token::PlusEq,
+                    '-' => Token::MinusEq,
+                    '*' => Token::StarEq,
+                    _ => Token::SlashEq,
+                }
+            }
             '+' => Token::Plus,
+            '-' => Token::Minus,
             '*' => Token::Star,
             '/' => Token::Slash,
             '%' => Token::Percent,
-            '.' => Token::Dot,
+            '.' => {
+                if chars.next_if_eq(&'.').is_some() {
+                    span.end += 1;
+                    span.column += 1;
+
+                    if chars.next_if_eq(&'=').is_some() {
+                        span.end += 1;
+                        span.column += 1;
+
+                        Token::DotDotEq
+                    } else {
+                        Token::DotDot
+                    }
+                } else {
+                    Token::Dot
+                }
+            }
             ',' => Token::Comma,
             '#' => Token::Pound,
-            '>' => Token::Greater,
-            '<' => Token::Less,
-            '&' if chars.next_if_eq(&'&').is_some() => {
-                span.end += 1;
-                span.column += 1;
+            '?' => Token::Question,
+            '^' => Token::Caret,
+            '>' => {
+                if chars.next_if_eq(&'=').is_some() {
+                    span.end += 1;
+                    span.column += 1;
+
+                    Token::GreaterEq
+                } else if chars.next_if_eq(&'>').is_some() {
+                    span.end += 1;
+                    span.column += 1;
 
-                Token::And
+                    Token::Shr
+                } else {
+                    Token::Greater
+                }
             }
-            '|' if chars.next_if_eq(&'|').is_some() => {
-                span.end += 1;
-                span.column += 1;
+            '<' => {
+                if chars.next_if_eq(&'=').is_some() {
+                    span.end += 1;
+                    span.column += 1;
+
+                    Token::LessEq
+                } else if chars.next_if_eq(&'<').is_some() {
+                    span.end += 1;
+                    span.column += 1;
+
+                    Token::Shl
+                } else {
+                    Token::Less
+                }
+            }
+            '&' => {
+                if chars.next_if_eq(&'&').is_some() {
+                    span.end += 1;
+                    span.column += 1;
 
-                Token::Or
+                    Token::And
+                } else {
+                    Token::Ampersand
+                }
+            }
+            '|' => {
+                if chars.next_if_eq(&'|').is_some() {
+                    span.end += 1;
+                    span.column += 1;
+
+                    Token::Or
+                } else {
+                    Token::Pipe
+                }
             }
             character => Token::Unknown(character),
         }
     }
 
-    fn parse_string(chars: &mut PeekMoreIterator<Chars>) -> (Token, usize, usize) {
+    /// Moves spans of `tokens` lexed separately from the source, so they point to the source part
+    /// starting at `at`.
+    fn shift_spans(tokens: &mut [Positioned<Token>], at: Span) {
+        for token in tokens {
+            if token.span.line == 0 {
+                token.span.column += at.column;
+            }
+
+            token.span.start += at.start;
+            token.span.end += at.start;
+            token.span.line += at.line;
+        }
+    }
+
+    /// Lexes string literal starting right after the opening quote at `span`.
+    fn parse_string(chars: &mut PeekMoreIterator<Chars>, span: Span) -> (Token, usize, usize) {
         let mut size = 0;
         let mut utf8size = 0;
         let mut data = String::new();
@@ -99,28 +187,39 @@ impl Lexer {
             size += 1;
             utf8size += character.len_utf8();
 
-            if character == '\\' {
-                if let Some(character) = chars.next() {
-                    size += 1;
-                    utf8size += character.len_utf8();
+            if character == '\\'
+                && let Some(character) = chars.next()
+            {
+                size += 1;
+                utf8size += character.len_utf8();
 
-                    data.push(character);
-                }
+                data.push(character);
             }
 
             if character == '{' {
                 datas.push(mem::take(&mut data));
 
-                formatted.push(
-                    iter::from_fn(|| {
-                        chars.next_if(|&s| s != '{' && s != '}').inspect(|value| {
-                            size += 1;
-                            utf8size += value.len_utf8();
-                        })
-                    })
-                    .collect::<String>(),
+                let at = Span::new(
+                    span.end + utf8size,
+                    span.end + utf8size,
+                    span.line,
+                    span.column + size,
                 );
 
+                let value = iter::from_fn(|| {
+                    chars.next_if(|&s| s != '{' && s != '}').inspect(|value| {
+                        size += 1;
+                        utf8size += value.len_utf8();
+                    })
+                })
+                .collect::<String>();
+
+                let mut tokens = Self::parse(value);
+
+                Self::shift_spans(&mut tokens, at);
+
+                formatted.push(tokens);
+
                 size += 1;
                 utf8size += 1;
 
@@ -138,11 +237,7 @@ impl Lexer {
             let mut parts = datas
                 .into_iter()
                 .map(StringPart::String)
-                .zip(
-                    formatted
-                        .into_iter()
-                        .map(|value| StringPart::Formatted(Self::parse(value))),
-                )
+                .zip(formatted.into_iter().map(StringPart::Formatted))
                 .fold(Vec::new(), |mut parts, tuple| {
                     parts.extend(<[StringPart; 2]>::from(tuple));
 
@@ -160,7 +255,6 @@ impl Lexer {
         tokens: &mut Vec<Positioned<Token>>,
         span: &mut Span,
         character: char,
-        neg: bool,
     ) {
         let mut value = iter::once(character)
             .chain(iter::from_fn(|| {
@@ -185,21 +279,10 @@ impl Lexer {
         span.end += value.len();
 
         if value.contains('.') {
-            let mut value: f32 = value.parse().unwrap();
-
-            if neg {
-                value = value.neg();
-            }
-
-            tokens.push(span.wrap(Token::Float(value)));
+            tokens.push(span.wrap(Token::Float(value.parse().unwrap())));
         } else {
-            let mut value: i64 = value.parse().unwrap();
-
-            if neg {
-                value = value.neg();
-            }
-
-            tokens.push(span.wrap(Token::Integer(value)));
+            // literals too large for any `int` are reported by parser
+            tokens.push(span.wrap(Token::Integer(value.parse().unwrap_or(u64::MAX))));
         }
 
         span.column += value.len();
@@ -215,12 +298,12 @@ impl Lexer {
 
         while let Some(character) = chars.next() {
             match character {
-                'A'..='z' => {
+                'A'..='Z' | 'a'..='z' | '_' => {
                     let ident = iter::once(character)
                         .chain(iter::from_fn(|| {
                             chars
                                 .by_ref()
-                                .next_if(|s| s.is_ascii_alphanumeric() || s == &'-' || s == &'_')
+                                .next_if(|s| s.is_ascii_alphanumeric() || s == &'_')
                         }))
                         .collect::<String>();
 
@@ -231,26 +314,14 @@ impl Lexer {
                     tokens.push(span.wrap(Self::parse_reserved(ident)));
                 }
                 '0'..='9' => {
-                    Self::parse_number(&mut chars, &mut tokens, &mut span, character, false);
-                }
-                '-' => {
-                    if let Some(character) = chars.next_if(char::is_ascii_digit) {
-                        Self::parse_number(&mut chars, &mut tokens, &mut span, character, true);
-                    } else {
-                        span.start = span.end;
-                        span.end += 1;
-
-                        tokens.push(span.wrap(Token::Minus));
-
-                        span.column += 1;
-                    }
+                    Self::parse_number(&mut chars, &mut tokens, &mut span, character);
                 }
                 '"' => {
                     span.start = span.end;
                     span.end += 1;
                     span.column += 1;
 
-                    let (value, size, utf8size) = Self::parse_string(&mut chars);
+                    let (value, size, utf8size) = Self::parse_string(&mut chars, span);
 
                     span.start = span.end;
                     span.end += utf8size + 1;
@@ -23,11 +23,12 @@ pub enum Token {
     #[display("{_0}")]
     #[is_variant]
     #[unwrap]
-    Float(f32),
+    Float(f64),
     #[display("{_0}")]
     #[is_variant]
     #[unwrap]
-    Integer(i64),
+    /// Magnitude of integer literal, its sign is a separate token.
+    Integer(u64),
     #[display("{_0}")]
     #[is_variant]
     #[unwrap]
@@ -36,6 +37,8 @@ pub enum Token {
     Record,
     #[display("struct")]
     Struct,
+    #[display("enum")]
+    Enum,
     #[display("const")]
     Const,
     #[display("let")]
@@ -46,6 +49,16 @@ pub enum Token {
     For,
     #[display("in")]
     In,
+    #[display("break")]
+    Break,
+    #[display("continue")]
+    Continue,
+    #[display("return")]
+    Return,
+    #[display("as")]
+    As,
+    #[display("none")]
+    None,
     #[display("if")]
     If,
     #[display("else")]
@@ -74,10 +87,18 @@ pub enum Token {
     Minus,
     #[display("+")]
     Plus,
+    #[display("+=")]
+    PlusEq,
+    #[display("-=")]
+    MinusEq,
     #[display("/")]
     Slash,
+    #[display("/=")]
+    SlashEq,
     #[display("*")]
     Star,
+    #[display("*=")]
+    StarEq,
     #[display("=>")]
     FatArrow,
     #[display("=")]
@@ -90,20 +111,40 @@ pub enum Token {
     Not,
     #[display("||")]
     Or,
+    #[display("|")]
+    Pipe,
     #[display("&&")]
     And,
+    #[display("&")]
+    Ampersand,
+    #[display("^")]
+    Caret,
     #[display("#")]
     Pound,
+    #[display("?")]
+    Question,
     #[display("%")]
     Percent,
     #[display(",")]
     Comma,
     #[display("<")]
     Less,
+    #[display("<=")]
+    LessEq,
+    #[display("<<")]
+    Shl,
     #[display(">")]
     Greater,
+    #[display(">=")]
+    GreaterEq,
+    #[display(">>")]
+    Shr,
     #[display(".")]
     Dot,
+    #[display("..")]
+    DotDot,
+    #[display("..=")]
+    DotDotEq,
     #[display("new")]
     New,
     #[display("<EOF>")]
@@ -128,9 +169,9 @@ impl Token {
     }
 
     #[must_use]
-    pub const fn is_usize(&self) -> bool {
+    pub fn is_usize(&self) -> bool {
         if let Self::Integer(value) = self {
-            *value >= 0
+            usize::try_from(*value).is_ok()
         } else {
             false
         }
@@ -3,20 +3,15 @@ mod statement;
 pub mod prelude {
     pub use crate::{Ident, Parse, ParseError, ParseResult, ParseResultExt, Parser, statement::*};
     pub use tapt_lexer::{Lexer, StringPart, Token};
-    pub use tapt_shared::{Positioned, Span};
+    pub use tapt_shared::{Diagnostic, Label, Positioned, Span};
     pub use tapt_typing::*;
 }
 
 use peekmore::{PeekMore, PeekMoreIterator};
-use std::{
-    error::Error,
-    fmt,
-    ops::{Deref, Range},
-    vec::IntoIter,
-};
+use std::{error::Error, fmt, ops::Deref, vec::IntoIter};
 use tapt_lexer::{Lexer, Token};
 use tapt_shared::{Positioned, Span};
-use tapt_typing::Type;
+use tapt_typing::{FunctionType, Type};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ParseError(String, Option<Span>);
@@ -92,6 +87,18 @@ impl ParseError {
         )
     }
 
+    /// Returns the error which was found further in the input, preferring `self` if both are at the same place.
+    #[must_use]
+    pub fn furthest(self, other: Self) -> Self {
+        let end = |error: &Self| error.1.map_or(usize::MAX, |span| span.start);
+
+        if end(&other) > end(&self) {
+            other
+        } else {
+            self
+        }
+    }
+
     #[must_use]
     pub fn too_much_tokens(tokens: &[Positioned<Token>]) -> Self {
         Self(
@@ -109,6 +116,12 @@ pub type ParseResult<T> = std::result::Result<T, ParseError>;
 pub trait ParseResultExt {
     #[must_use]
     fn message<T: Into<String>>(self, message: T) -> Self;
+
+    /// Tries to parse with `other` if parsing failed without `consumed` tokens, keeping the error which got further.
+    ///
+    /// Once tokens are consumed, other alternatives would start in the middle of the failed one, so its error is returned.
+    #[must_use]
+    fn or_parse<F: FnOnce() -> Self>(self, consumed: bool, other: F) -> Self;
 }
 
 impl<V> ParseResultExt for ParseResult<V> {
@@ -119,11 +132,23 @@ impl<V> ParseResultExt for ParseResult<V> {
 
         self
     }
+
+    fn or_parse<F: FnOnce() -> Self>(self, consumed: bool, other: F) -> Self {
+        match self {
+            Ok(value) => Ok(value),
+            Err(error) if consumed => Err(error),
+            Err(error) => other().map_err(|other| error.furthest(other)),
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct Parser {
     tokens: PeekMoreIterator<IntoIter<Positioned<Token>>>,
+    /// Errors parser has recovered from.
+    errors: Vec<ParseError>,
+    /// Number of consumed tokens.
+    position: usize,
 }
 
 impl Parser {
@@ -131,12 +156,77 @@ impl Parser {
     pub fn new(tokens: Vec<Positioned<Token>>) -> Self {
         Self {
             tokens: tokens.into_iter().peekmore(),
+            errors: Vec::new(),
+            position: 0,
+        }
+    }
+
+    /// Returns the number of tokens consumed so far.
+    #[must_use]
+    pub const fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Records error, so parsing can continue after it.
+    pub fn report(&mut self, error: ParseError) {
+        self.errors.push(error);
+    }
+
+    /// Returns errors reported since the last call, ordered by their position.
+    pub fn take_errors(&mut self) -> Vec<ParseError> {
+        let mut errors = std::mem::take(&mut self.errors);
+
+        errors.sort_by_key(|error| error.1.map_or(usize::MAX, |span| span.start));
+
+        errors
+    }
+
+    /// Skips tokens until the start of the next statement after statement starting at `start` failed to parse.
+    pub fn synchronize(&mut self, start: Option<Span>) {
+        // the failed token is skipped, so parsing can't get stuck on it
+        if self
+            .peek()
+            .is_some_and(|token| Some(token.span) == start && token.value != Token::EOF)
+        {
+            self.next();
+        }
+
+        // blocks started after the error are skipped entirely
+        let mut depth = 0usize;
+
+        while let Some(token) = self.peek() {
+            match token.value {
+                Token::EOF => break,
+                Token::BraceOpen => depth += 1,
+                Token::BraceClose if depth > 0 => depth -= 1,
+                _ if depth > 0 => {}
+                Token::Semi => {
+                    self.next();
+
+                    break;
+                }
+                Token::BraceClose
+                | Token::Let
+                | Token::Const
+                | Token::Func
+                | Token::Struct
+                | Token::Record
+                | Token::Enum
+                | Token::For
+                | Token::While
+                | Token::Break
+                | Token::Continue
+                | Token::Return => break,
+                _ => {}
+            }
+
+            self.next();
         }
     }
 
     /// Consumes the current token only if it exists and is equal to `value`.
     pub fn try_consume(&mut self, value: &Token) -> bool {
-        self.tokens.next_if(|token| &token.value == value).is_some()
+        self.next_if(|token| token == value).is_some()
     }
 
     fn verify_nth(&mut self, index: usize, token: &Token) -> ParseResult<()> {
@@ -205,6 +295,13 @@ impl Parser {
         self.verify_nth_if(2, func)
     }
 
+    /// # Errors
+    ///
+    /// Will return an error if "func" return false.
+    pub fn verify4_if<F: Fn(&Token) -> bool>(&mut self, func: F) -> ParseResult<()> {
+        self.verify_nth_if(3, func)
+    }
+
     /// Checks if the next token exists and it is equal to `value`.
     pub fn check(&mut self, value: &Token) -> bool {
         self.check_if(|v| v == value)
@@ -295,7 +392,11 @@ impl Parser {
     /// Consumes the current token and returns it wrapped in `Some` if it exists, otherwise returning `None`.
     #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> Option<Positioned<Token>> {
-        self.tokens.next()
+        let token = self.tokens.next();
+
+        self.position += usize::from(token.is_some());
+
+        token
     }
 
     /// Peeks the current token and returns a reference to it wrapped in `Some` if it exists, otherwise returning `None`.
@@ -305,7 +406,11 @@ impl Parser {
 
     /// Consumes the current token and returns it wrapped in `Some` if the result of the `func` function is `true`, otherwise returning `None`.
     pub fn next_if<F: Fn(&Token) -> bool>(&mut self, func: F) -> Option<Positioned<Token>> {
-        self.tokens.next_if(|value| func(&value.value))
+        let token = self.tokens.next_if(|value| func(&value.value));
+
+        self.position += usize::from(token.is_some());
+
+        token
     }
 
     #[must_use]
@@ -447,7 +552,7 @@ pub trait Parse: Sized {
     }
 }
 
-impl Parse for f32 {
+impl Parse for f64 {
     fn parse(parser: &mut Parser) -> ParseResult<Positioned<Self>> {
         parser
             .consume_if(Token::is_float)
@@ -465,9 +570,19 @@ impl Parse for usize {
 
 impl Parse for i64 {
     fn parse(parser: &mut Parser) -> ParseResult<Positioned<Self>> {
-        parser
-            .consume_if(Token::is_integer)
-            .map(|Positioned { value, span }| span.wrap(value.unwrap_integer()))
+        let Positioned { value, span } = parser.consume_if(Token::is_integer)?;
+
+        // parsing goes on, so the rest of the errors are found too
+        let value = Self::try_from(value.unwrap_integer()).unwrap_or_else(|_| {
+            parser.report(ParseError::new(
+                "Integer literal doesn't fit into `int`",
+                Some(span),
+            ));
+
+            Self::MAX
+        });
+
+        Ok(span.wrap(value))
     }
 }
 
@@ -487,21 +602,6 @@ impl Parse for bool {
     }
 }
 
-impl Parse for Range<usize> {
-    fn parse(parser: &mut Parser) -> ParseResult<Positioned<Self>> {
-        parser.verify_if(Token::is_usize)?;
-        parser.verify2(&Token::Dot)?;
-        parser.verify3(&Token::Dot)?;
-
-        let start = usize::parse(parser)?;
-
-        parser.consume(&Token::Dot)?;
-        parser.consume(&Token::Dot)?;
-
-        usize::parse(parser).map(|end| start.between(&end).wrap(start.value..end.value))
-    }
-}
-
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Ident(pub String);
 
@@ -533,11 +633,43 @@ impl<T: Parse> Parse for Vec<Positioned<T>> {
     }
 }
 
+/// Parses type of function, `func(int, string): bool`.
+fn parse_function_type(parser: &mut Parser) -> ParseResult<Positioned<Type>> {
+    let start = parser.consume(&Token::Func)?;
+
+    let args =
+        Type::parse_separated_in(parser, &Token::Comma, &Token::ParenOpen, &Token::ParenClose)?;
+
+    let (end, output_type) = if parser.try_consume(&Token::Colon) {
+        let ty = Type::parse(parser)?;
+
+        (ty.span, ty.value)
+    } else {
+        (args.span, Type::None)
+    };
+
+    Ok(start.span.between(end).wrap(Type::Function(FunctionType {
+        args: args.value.into_iter().map(|value| value.value).collect(),
+        output_type: Box::new(output_type),
+    })))
+}
+
 impl Parse for Type {
     fn parse(parser: &mut Parser) -> ParseResult<Positioned<Self>> {
-        parser
-            .consume_if(|value| value.is_ident_and(|value| value == "int"))
-            .map(|value| value.wrap(Self::Integer))
+        let mut ty = parser
+            .consume(&Token::BracketOpen)
+            .and_then(|start| {
+                let ty = Self::parse(parser)?;
+                let end = parser.consume(&Token::BracketClose)?;
+
+                Ok(start.between(&end).wrap(Self::Array(Box::new(ty.value))))
+            })
+            .or_else(|_| parse_function_type(parser))
+            .or_else(|_| {
+                parser
+                    .consume_if(|value| value.is_ident_and(|value| value == "int"))
+                    .map(|value| value.wrap(Self::Integer))
+            })
             .or_else(|_| {
                 parser
                     .consume_if(|value| value.is_ident_and(|value| value == "float"))
@@ -553,5 +685,21 @@ impl Parse for Type {
                     .consume_if(|value| value.is_ident_and(|value| value == "string"))
                     .map(|value| value.wrap(Self::String))
             })
+            .or_else(|_| {
+                parser
+                    .consume_if(|value| value.is_ident_and(|value| value == "range"))
+                    .map(|value| value.wrap(Self::Range))
+            })
+            .or_else(|_| {
+                Ident::parse(parser).map(|value| value.map(|Ident(name)| Self::Named(name)))
+            })?;
+
+        while let Ok(question) = parser.consume(&Token::Question) {
+            let span = ty.between(&question);
+
+            ty = span.wrap(Self::Optional(Box::new(ty.value)));
+        }
+
+        Ok(ty)
     }
 }
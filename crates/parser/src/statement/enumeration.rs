@@ -0,0 +1,125 @@
+use crate::prelude::*;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnumVariantFields {
+    Unit,
+    Tuple(Vec<Positioned<Type>>),
+    Struct(Vec<Positioned<StructField>>),
+}
+
+impl fmt::Display for EnumVariantFields {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unit => Ok(()),
+            Self::Tuple(fields) => write!(
+                f,
+                "({})",
+                fields
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Self::Struct(fields) => write!(
+                f,
+                " {{ {} }}",
+                fields
+                    .iter()
+                    .map(|field| format!("{}: {}", field.value.name, field.value.ty))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumVariant {
+    pub name: Positioned<Ident>,
+    pub fields: EnumVariantFields,
+}
+
+impl fmt::Display for EnumVariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.name, self.fields)
+    }
+}
+
+impl Parse for EnumVariant {
+    fn parse(parser: &mut Parser) -> ParseResult<Positioned<Self>> {
+        let name = Ident::parse(parser)?;
+
+        if parser.check(&Token::ParenOpen) {
+            let fields = Type::parse_separated_in(
+                parser,
+                &Token::Comma,
+                &Token::ParenOpen,
+                &Token::ParenClose,
+            )?;
+
+            Ok(name.between(&fields).wrap(Self {
+                name,
+                fields: EnumVariantFields::Tuple(fields.value),
+            }))
+        } else if parser.check(&Token::BraceOpen) {
+            let fields = StructField::parse_separated_in(
+                parser,
+                &Token::Comma,
+                &Token::BraceOpen,
+                &Token::BraceClose,
+            )?;
+
+            Ok(name.between(&fields).wrap(Self {
+                name,
+                fields: EnumVariantFields::Struct(fields.value),
+            }))
+        } else {
+            Ok(name.span.wrap(Self {
+                name,
+                fields: EnumVariantFields::Unit,
+            }))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumStatement {
+    pub name: Positioned<Ident>,
+    pub variants: Positioned<Vec<Positioned<EnumVariant>>>,
+}
+
+impl fmt::Display for EnumStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "enum {} {{\n{}\n}}",
+            self.name,
+            self.variants
+                .value
+                .iter()
+                .map(|variant| format!("  {variant}"))
+                .collect::<Vec<_>>()
+                .join(",\n")
+        )
+    }
+}
+
+impl Parse for EnumStatement {
+    fn parse(parser: &mut Parser) -> ParseResult<Positioned<Self>> {
+        parser.verify(&Token::Enum)?;
+        parser.verify2_if(Token::is_ident)?;
+
+        let start = parser.consume(&Token::Enum)?;
+        let name = Ident::parse(parser)?;
+
+        let variants = EnumVariant::parse_separated_in(
+            parser,
+            &Token::Comma,
+            &Token::BraceOpen,
+            &Token::BraceClose,
+        )?;
+
+        Ok(start.between(&variants).wrap(Self { name, variants }))
+    }
+}
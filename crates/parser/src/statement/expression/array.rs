@@ -3,7 +3,7 @@ use std::fmt;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct ArrayExpr {
-    values: Vec<Positioned<Expression>>,
+    pub values: Vec<Positioned<Expression>>,
 }
 
 impl fmt::Display for ArrayExpr {
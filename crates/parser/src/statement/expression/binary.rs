@@ -10,53 +10,151 @@ pub struct BinaryExpression {
 
 impl fmt::Display for BinaryExpression {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} {} {}", self.lhs, self.operator, self.rhs)
+        let precedence = self.operator.value.precedence();
+        let right_assoc = precedence == Precedence::PAssign;
+
+        write_operand(f, &self.lhs.value, |other| {
+            other < precedence || (right_assoc && other == precedence)
+        })?;
+        write!(f, " {} ", self.operator)?;
+        write_operand(f, &self.rhs.value, |other| {
+            other < precedence || (!right_assoc && other == precedence)
+        })
+    }
+}
+
+/// Writes operand of binary expression, wrapping it into parentheses if operator of the operand
+/// binds weaker than `needs_parens` allows.
+fn write_operand(
+    f: &mut fmt::Formatter<'_>,
+    operand: &Expression,
+    needs_parens: impl Fn(Precedence) -> bool,
+) -> fmt::Result {
+    match operand {
+        Expression::Binary(value) if needs_parens(value.operator.value.precedence()) => {
+            write!(f, "({value})")
+        }
+        Expression::Range(value) if needs_parens(Precedence::PRange) => write!(f, "({value})"),
+        Expression::Cast(value) if needs_parens(Precedence::PCast) => write!(f, "({value})"),
+        value => fmt::Display::fmt(value, f),
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Operator {
-    Assign,      // =
-    Add,         // +
-    Sub,         // -
-    Mul,         // *
-    Div,         // /
-    Equal,       // ==
-    NotEqual,    // !=
-    LessThan,    // <
-    GreaterThan, // >
-    And,         // &&
-    Or,          // ||
+    Assign,       // =
+    AddAssign,    // +=
+    SubAssign,    // -=
+    MulAssign,    // *=
+    DivAssign,    // /=
+    Add,          // +
+    Sub,          // -
+    Mul,          // *
+    Div,          // /
+    Rem,          // %
+    Equal,        // ==
+    NotEqual,     // !=
+    LessThan,     // <
+    LessEqual,    // <=
+    GreaterThan,  // >
+    GreaterEqual, // >=
+    BitAnd,       // &
+    BitOr,        // |
+    BitXor,       // ^
+    Shl,          // <<
+    Shr,          // >>
+    And,          // &&
+    Or,           // ||
 }
 
 impl fmt::Display for Operator {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str(match self {
             Self::Assign => "=",
+            Self::AddAssign => "+=",
+            Self::SubAssign => "-=",
+            Self::MulAssign => "*=",
+            Self::DivAssign => "/=",
             Self::Add => "+",
             Self::Sub => "-",
             Self::Mul => "*",
             Self::Div => "/",
+            Self::Rem => "%",
             Self::Equal => "==",
             Self::NotEqual => "!=",
             Self::LessThan => "<",
+            Self::LessEqual => "<=",
             Self::GreaterThan => ">",
+            Self::GreaterEqual => ">=",
+            Self::BitAnd => "&",
+            Self::BitOr => "|",
+            Self::BitXor => "^",
+            Self::Shl => "<<",
+            Self::Shr => ">>",
             Self::And => "&&",
             Self::Or => "||",
         })
     }
 }
 
+impl Operator {
+    /// Returns how tight the operator binds its operands.
+    #[must_use]
+    pub const fn precedence(&self) -> Precedence {
+        match self {
+            Self::Assign
+            | Self::AddAssign
+            | Self::SubAssign
+            | Self::MulAssign
+            | Self::DivAssign => Precedence::PAssign,
+            Self::Or => Precedence::POr,
+            Self::And => Precedence::PAnd,
+            Self::Equal | Self::NotEqual => Precedence::PEquals,
+            Self::LessThan | Self::LessEqual | Self::GreaterThan | Self::GreaterEqual => {
+                Precedence::PLessGreater
+            }
+            Self::BitOr => Precedence::PBitOr,
+            Self::BitXor => Precedence::PBitXor,
+            Self::BitAnd => Precedence::PBitAnd,
+            Self::Shl | Self::Shr => Precedence::PShift,
+            Self::Add | Self::Sub => Precedence::PSum,
+            Self::Mul | Self::Div | Self::Rem => Precedence::PProduct,
+        }
+    }
+
+    /// Returns operator combining current value with the assigned one, if `self` is a compound
+    /// assignment like `+=`.
+    #[must_use]
+    pub const fn compound(&self) -> Option<Self> {
+        match self {
+            Self::AddAssign => Some(Self::Add),
+            Self::SubAssign => Some(Self::Sub),
+            Self::MulAssign => Some(Self::Mul),
+            Self::DivAssign => Some(Self::Div),
+            _ => None,
+        }
+    }
+}
+
 impl BinaryExpression {
+    /// Parses right operand of `operator` which has the given `precedence`.
+    ///
     /// # Errors
-    /// 
+    ///
     /// Returns `ParseError` if parsing failed
     pub fn parse(
         parser: &mut Parser,
         lhs: Positioned<Expression>,
         operator: Positioned<Operator>,
+        precedence: Precedence,
     ) -> ParseResult<Positioned<Self>> {
-        let rhs = Expression::parse(parser)?;
+        // assignment is right-associative, so `a = b = c` is parsed as `a = (b = c)` and the
+        // compiler can reject assigning an assignment
+        let rhs = if precedence == Precedence::PAssign {
+            Expression::parse_with_precedence(parser, Precedence::PLowest)?
+        } else {
+            Expression::parse_with_precedence(parser, precedence)?
+        };
 
         Ok(lhs.between(&rhs).wrap(Self { lhs, rhs, operator }))
     }
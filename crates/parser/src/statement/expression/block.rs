@@ -28,15 +28,30 @@ pub type Statements = (Vec<Positioned<Statement>>, Option<Positioned<Statement>>
 impl Block {
     /// Note: this method will not consume `until` token.
     ///
-    /// # Errors
-    ///
-    /// Returns parsing error if parsing fails.
-    pub fn parse_statements_until(parser: &mut Parser, until: &Token) -> ParseResult<Statements> {
+    /// Statements which failed to parse are skipped, errors are reported to the parser
+    /// and can be retrieved with `Parser::take_errors`.
+    pub fn parse_statements_until(parser: &mut Parser, until: &Token) -> Statements {
         let mut statements = Vec::new();
         let mut return_statement: Option<Positioned<Statement>> = None;
 
-        while !parser.check(until) {
-            let statement = Statement::parse(parser)?;
+        while !parser.check(until) && !parser.check(&Token::EOF) {
+            let start = parser.peek().map(|token| token.span);
+
+            let statement = match Statement::parse(parser) {
+                Ok(statement) => statement,
+                Err(error) => {
+                    parser.report(error);
+                    parser.synchronize(start);
+
+                    continue;
+                }
+            };
+
+            if let Some(previous) = return_statement.take() {
+                parser.report(ParseError::new("missing ;", Some(previous.span)));
+
+                statements.push(previous);
+            }
 
             if parser.try_consume(&Token::Semi)
                 || matches!(
@@ -46,21 +61,16 @@ impl Block {
                         | Statement::WhileLoop(_)
                         | Statement::Record(_)
                         | Statement::Struct(_)
+                        | Statement::Enum(_)
                 )
             {
-                if let Some(statement) = &return_statement {
-                    return Err(ParseError::new("missing ;", Some(statement.span)));
-                }
-
                 statements.push(statement);
-            } else if let Some(statement) = &return_statement {
-                return Err(ParseError::new("missing ;", Some(statement.span)));
             } else {
                 return_statement = Some(statement);
             }
         }
 
-        Ok((statements, return_statement))
+        (statements, return_statement)
     }
 }
 
@@ -69,7 +79,7 @@ impl Parse for Block {
         let from = parser.consume(&Token::BraceOpen)?;
 
         let (statements, return_statement) =
-            Self::parse_statements_until(parser, &Token::BraceClose)?;
+            Self::parse_statements_until(parser, &Token::BraceClose);
         let return_statement = return_statement.map(Box::new);
 
         let to = parser.consume(&Token::BraceClose)?;
@@ -25,7 +25,7 @@ impl fmt::Display for FunctionCall {
 
 impl FunctionCall {
     /// # Errors
-    /// 
+    ///
     /// Returns `ParseError` if parsing failed
     pub fn parse(
         parser: &mut Parser,
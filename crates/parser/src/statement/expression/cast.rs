@@ -0,0 +1,36 @@
+use crate::prelude::*;
+use std::fmt;
+
+/// Conversion of value to another type, `value as float`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CastExpression {
+    pub value: Positioned<Expression>,
+    pub ty: Positioned<Type>,
+}
+
+impl fmt::Display for CastExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.value.value {
+            Expression::Binary(_) | Expression::Range(_) => {
+                write!(f, "({}) as {}", self.value, self.ty)
+            }
+            _ => write!(f, "{} as {}", self.value, self.ty),
+        }
+    }
+}
+
+impl CastExpression {
+    /// # Errors
+    ///
+    /// Returns `ParseError` if parsing failed
+    pub fn parse(
+        parser: &mut Parser,
+        value: Positioned<Expression>,
+    ) -> ParseResult<Positioned<Self>> {
+        parser.consume(&Token::As)?;
+
+        let ty = Type::parse(parser)?;
+
+        Ok(value.between(&ty).wrap(Self { value, ty }))
+    }
+}
@@ -0,0 +1,83 @@
+use crate::prelude::*;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormattedPart {
+    String(String),
+    Value(Box<Positioned<Expression>>),
+}
+
+impl fmt::Display for FormattedPart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::String(value) => value.escape_debug().fmt(f),
+            Self::Value(value) => write!(f, "{{{value}}}"),
+        }
+    }
+}
+
+/// String with values interpolated into it, `"hello {name}"`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormattedString {
+    pub parts: Vec<FormattedPart>,
+}
+
+impl fmt::Display for FormattedString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("\"")?;
+
+        for part in &self.parts {
+            part.fmt(f)?;
+        }
+
+        f.write_str("\"")
+    }
+}
+
+impl FormattedString {
+    /// Parses expression from tokens placed between braces of the string.
+    fn parse_value(
+        parser: &mut Parser,
+        tokens: Vec<Positioned<Token>>,
+    ) -> ParseResult<Positioned<Expression>> {
+        let mut inner = Parser::new(tokens);
+
+        let value = Expression::parse(&mut inner);
+
+        // errors recovered inside of the interpolated blocks
+        for error in inner.take_errors() {
+            parser.report(error);
+        }
+
+        let value = value?;
+
+        inner.consume(&Token::EOF)?;
+
+        Ok(value)
+    }
+}
+
+impl Parse for FormattedString {
+    fn parse(parser: &mut Parser) -> ParseResult<Positioned<Self>> {
+        let Positioned {
+            value: Token::FormattedString(parts),
+            span,
+        } = parser.consume_if(Token::is_formatted_string)?
+        else {
+            unreachable!()
+        };
+
+        let parts = parts
+            .into_iter()
+            .filter(|part| !matches!(part, StringPart::String(value) if value.is_empty()))
+            .map(|part| match part {
+                StringPart::String(value) => Ok(FormattedPart::String(value)),
+                StringPart::Formatted(tokens) => Self::parse_value(parser, tokens)
+                    .map(Box::new)
+                    .map(FormattedPart::Value),
+            })
+            .collect::<ParseResult<_>>()?;
+
+        Ok(span.wrap(Self { parts }))
+    }
+}
@@ -0,0 +1,63 @@
+use crate::prelude::*;
+use std::fmt;
+
+/// Anonymous function, `func(x: int): int { x + 1 }`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuncExpression {
+    pub args: Positioned<Vec<Positioned<FuncArg>>>,
+    pub output_type: Option<Positioned<Type>>,
+    pub body: Positioned<Block>,
+}
+
+impl fmt::Display for FuncExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "func({})",
+            self.args
+                .value
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+
+        if let Some(output_type) = &self.output_type {
+            write!(f, ": {output_type} ")?;
+        } else {
+            f.write_str(" ")?;
+        }
+
+        write!(f, "{}", self.body)
+    }
+}
+
+impl Parse for FuncExpression {
+    fn parse(parser: &mut Parser) -> ParseResult<Positioned<Self>> {
+        parser.verify(&Token::Func)?;
+        parser.verify2(&Token::ParenOpen)?;
+
+        let start = parser.consume(&Token::Func)?;
+
+        let args = FuncArg::parse_separated_in(
+            parser,
+            &Token::Comma,
+            &Token::ParenOpen,
+            &Token::ParenClose,
+        )?;
+
+        let output_type = if parser.try_consume(&Token::Colon) {
+            Some(Type::parse(parser)?)
+        } else {
+            None
+        };
+
+        let body = Block::parse(parser)?;
+
+        Ok(start.between(&body).wrap(Self {
+            args,
+            output_type,
+            body,
+        }))
+    }
+}
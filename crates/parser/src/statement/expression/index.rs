@@ -44,7 +44,11 @@ pub struct IndexExpression {
 
 impl fmt::Display for IndexExpression {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}.{}", self.target, self.index)
+        if let IndexKind::Expr(index) = &self.index.value {
+            write!(f, "{}[{index}]", self.target)
+        } else {
+            write!(f, "{}.{}", self.target, self.index)
+        }
     }
 }
 
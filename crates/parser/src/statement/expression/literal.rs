@@ -1,9 +1,9 @@
 use crate::prelude::*;
-use std::{fmt, ops::Range};
+use std::fmt;
 
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub enum Number {
-    Float(f32),
+    Float(f64),
     Int(i64),
 }
 
@@ -18,7 +18,19 @@ impl fmt::Display for Number {
 
 impl Parse for Number {
     fn parse(parser: &mut Parser) -> ParseResult<Positioned<Self>> {
-        f32::parse(parser)
+        // magnitude of the smallest `int` doesn't fit into it, so it can only be parsed negated,
+        // unless the literal is an operand of postfix operator like `.` and isn't negated itself
+        if parser.check(&Token::Minus)
+            && parser.check2(&Token::Integer(i64::MIN.unsigned_abs()))
+            && !parser.check3_if(|token| {
+                matches!(token, Token::ParenOpen | Token::BracketOpen | Token::Dot)
+            })
+            && let (Some(minus), Some(value)) = (parser.next(), parser.next())
+        {
+            return Ok(minus.between(&value).wrap(Self::Int(i64::MIN)));
+        }
+
+        f64::parse(parser)
             .map(|Positioned { value, span }| span.wrap(Self::Float(value)))
             .or_else(|_| {
                 i64::parse(parser).map(|Positioned { value, span }| span.wrap(Self::Int(value)))
@@ -32,7 +44,7 @@ pub enum Literal {
     // Length(Length),
     String(String),
     Boolean(bool),
-    Range(Range<usize>),
+    None,
 }
 
 impl fmt::Display for Literal {
@@ -41,7 +53,7 @@ impl fmt::Display for Literal {
             Self::Number(value) => value.fmt(f),
             Self::String(value) => write!(f, "{value:?}"),
             Self::Boolean(value) => value.fmt(f),
-            Self::Range(value) => write!(f, "{}..{}", value.start, value.end),
+            Self::None => f.write_str("none"),
         }
     }
 }
@@ -51,7 +63,9 @@ impl Parse for Literal {
         bool::parse(parser)
             .map(|Positioned { value, span }| span.wrap(Self::Boolean(value)))
             .or_else(|_| {
-                Range::parse(parser).map(|Positioned { value, span }| span.wrap(Self::Range(value)))
+                parser
+                    .consume(&Token::None)
+                    .map(|value| value.wrap(Self::None))
             })
             .or_else(|_| {
                 Number::parse(parser)
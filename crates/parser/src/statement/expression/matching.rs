@@ -1,50 +1,40 @@
 use crate::prelude::*;
 use std::fmt;
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum MatchCase {
-    Ident(Ident),
-    Value(Expression),
-}
-
-impl fmt::Display for MatchCase {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::Ident(value) => value.fmt(f),
-            Self::Value(value) => value.fmt(f),
-        }
-    }
-}
-
-impl Parse for MatchCase {
-    fn parse(parser: &mut Parser) -> ParseResult<Positioned<Self>> {
-        Ident::parse(parser)
-            .map(|value| value.map(Self::Ident))
-            .or_else(|_| Expression::parse(parser).map(|value| value.map(Self::Value)))
-    }
-}
-
 #[derive(Debug, Clone, PartialEq)]
 pub struct MatchVariant {
-    pub case: Positioned<MatchCase>,
+    pub case: Positioned<Pattern>,
+    pub guard: Option<Positioned<Expression>>,
     pub then: Positioned<Expression>,
 }
 
 impl fmt::Display for MatchVariant {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} => {}", self.case, self.then)
+        self.case.fmt(f)?;
+
+        if let Some(guard) = &self.guard {
+            write!(f, " if {guard}")?;
+        }
+
+        write!(f, " => {}", self.then)
     }
 }
 
 impl Parse for MatchVariant {
     fn parse(parser: &mut Parser) -> ParseResult<Positioned<Self>> {
-        let case = MatchCase::parse(parser)?;
+        let case = Pattern::parse(parser)?;
+
+        let guard = if parser.try_consume(&Token::If) {
+            Some(Expression::parse(parser)?)
+        } else {
+            None
+        };
 
         parser.consume(&Token::FatArrow)?;
 
         let then = Expression::parse(parser)?;
 
-        Ok(case.between(&then).wrap(Self { case, then }))
+        Ok(case.between(&then).wrap(Self { case, guard, then }))
     }
 }
 
@@ -2,35 +2,55 @@ mod array;
 mod binary;
 mod block;
 mod call;
+mod cast;
+mod formatted;
+mod func;
 mod if_else;
 mod index;
 mod literal;
 mod matching;
 mod new;
 mod object;
+mod range;
+mod unary;
 
 pub use self::{
     array::ArrayExpr,
     binary::{BinaryExpression, Operator},
     block::Block,
     call::FunctionCall,
+    cast::CastExpression,
+    formatted::{FormattedPart, FormattedString},
+    func::FuncExpression,
     if_else::IfElseExpression,
     index::{IndexExpression, IndexKind},
     literal::{Literal, Number},
-    matching::{MatchCase, MatchExpression, MatchVariant},
+    matching::{MatchExpression, MatchVariant},
     new::{InstanceArgs, NewInstanceExpression, StructFieldValue},
     object::{ObjectExpr, ObjectProperty},
+    range::RangeExpression,
+    unary::{UnaryExpression, UnaryOperator},
 };
 use crate::prelude::*;
 use std::fmt;
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Hash)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy, Hash)]
 pub enum Precedence {
     PLowest,
+    PAssign,
+    POr,
+    PAnd,
     PEquals,
+    PRange,
     PLessGreater,
+    PBitOr,
+    PBitXor,
+    PBitAnd,
+    PShift,
     PSum,
     PProduct,
+    PCast,
+    PPrefix,
     PCall,
     PIndex,
 }
@@ -38,21 +58,43 @@ pub enum Precedence {
 impl Precedence {
     const fn from_ref(token: &Positioned<Token>) -> (Self, Option<Positioned<Operator>>) {
         match &token.value {
-            Token::Eq => (Self::PEquals, Some(token.span.wrap(Operator::Assign))),
+            Token::Eq => (Self::PAssign, Some(token.span.wrap(Operator::Assign))),
+            Token::PlusEq => (Self::PAssign, Some(token.span.wrap(Operator::AddAssign))),
+            Token::MinusEq => (Self::PAssign, Some(token.span.wrap(Operator::SubAssign))),
+            Token::StarEq => (Self::PAssign, Some(token.span.wrap(Operator::MulAssign))),
+            Token::SlashEq => (Self::PAssign, Some(token.span.wrap(Operator::DivAssign))),
+            Token::Or => (Self::POr, Some(token.span.wrap(Operator::Or))),
+            Token::And => (Self::PAnd, Some(token.span.wrap(Operator::And))),
             Token::EqEq => (Self::PEquals, Some(token.span.wrap(Operator::Equal))),
             Token::NotEq => (Self::PEquals, Some(token.span.wrap(Operator::NotEqual))),
             Token::Less => (
                 Self::PLessGreater,
                 Some(token.span.wrap(Operator::LessThan)),
             ),
+            Token::LessEq => (
+                Self::PLessGreater,
+                Some(token.span.wrap(Operator::LessEqual)),
+            ),
             Token::Greater => (
                 Self::PLessGreater,
                 Some(token.span.wrap(Operator::GreaterThan)),
             ),
+            Token::GreaterEq => (
+                Self::PLessGreater,
+                Some(token.span.wrap(Operator::GreaterEqual)),
+            ),
+            Token::Pipe => (Self::PBitOr, Some(token.span.wrap(Operator::BitOr))),
+            Token::Caret => (Self::PBitXor, Some(token.span.wrap(Operator::BitXor))),
+            Token::Ampersand => (Self::PBitAnd, Some(token.span.wrap(Operator::BitAnd))),
+            Token::Shl => (Self::PShift, Some(token.span.wrap(Operator::Shl))),
+            Token::Shr => (Self::PShift, Some(token.span.wrap(Operator::Shr))),
             Token::Plus => (Self::PSum, Some(token.span.wrap(Operator::Add))),
             Token::Minus => (Self::PSum, Some(token.span.wrap(Operator::Sub))),
             Token::Star => (Self::PProduct, Some(token.span.wrap(Operator::Mul))),
             Token::Slash => (Self::PProduct, Some(token.span.wrap(Operator::Div))),
+            Token::Percent => (Self::PProduct, Some(token.span.wrap(Operator::Rem))),
+            Token::As => (Self::PCast, None),
+            Token::DotDot | Token::DotDotEq => (Self::PRange, None),
             Token::ParenOpen => (Self::PCall, None),
             Token::BracketOpen | Token::Dot => (Self::PIndex, None),
             _ => (Self::PLowest, None),
@@ -63,50 +105,49 @@ impl Precedence {
 fn go_parse_pratt_expr(
     parser: &mut Parser,
     precedence: Precedence,
-    left: Positioned<Expression>,
+    mut left: Positioned<Expression>,
 ) -> ParseResult<Positioned<Expression>> {
-    if let Some(value) = parser.peek() {
+    while let Some(value) = parser.peek() {
         let (p, operator) = Precedence::from_ref(value);
 
-        if operator.is_some() {
-            parser.next();
+        if p <= precedence {
+            break;
         }
 
-        match p {
-            Precedence::PCall if precedence < Precedence::PCall => {
-                let left = FunctionCall::parse(parser, left)?.map(Expression::FunctionCall);
-
-                go_parse_pratt_expr(parser, precedence, left)
-            }
-            Precedence::PIndex if precedence < Precedence::PIndex => {
-                let left = IndexExpression::parse(parser, left)?
-                    .map(Box::new)
-                    .map(Expression::Index);
-
-                go_parse_pratt_expr(parser, precedence, left)
-            }
-            ref peek_precedence if precedence < *peek_precedence => {
-                let left = if let Some(operator) = operator {
-                    BinaryExpression::parse(parser, left, operator)?
-                        .map(Box::new)
-                        .map(Expression::Binary)
-                } else {
-                    left
+        left = match p {
+            Precedence::PCall => FunctionCall::parse(parser, left)?.map(Expression::FunctionCall),
+            Precedence::PRange => RangeExpression::parse(parser, left, Precedence::PRange)?
+                .map(Box::new)
+                .map(Expression::Range),
+            Precedence::PCast => CastExpression::parse(parser, left)?
+                .map(Box::new)
+                .map(Expression::Cast),
+            Precedence::PIndex => IndexExpression::parse(parser, left)?
+                .map(Box::new)
+                .map(Expression::Index),
+            _ => {
+                let Some(operator) = operator else {
+                    break;
                 };
 
-                go_parse_pratt_expr(parser, precedence, left)
+                parser.next();
+
+                BinaryExpression::parse(parser, left, operator, p)?
+                    .map(Box::new)
+                    .map(Expression::Binary)
             }
-            _ => Ok(left),
-        }
-    } else {
-        Ok(left)
+        };
     }
+
+    Ok(left)
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expression {
     Literal(Literal),
+    Formatted(FormattedString),
     FunctionCall(FunctionCall),
+    Func(FuncExpression),
     Ident(Ident),
     Object(ObjectExpr),
     Array(ArrayExpr),
@@ -116,13 +157,18 @@ pub enum Expression {
     Match(MatchExpression),
     Binary(Box<BinaryExpression>),
     Index(Box<IndexExpression>),
+    Range(Box<RangeExpression>),
+    Unary(Box<UnaryExpression>),
+    Cast(Box<CastExpression>),
 }
 
 impl fmt::Display for Expression {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Literal(value) => value.fmt(f),
+            Self::Formatted(value) => value.fmt(f),
             Self::FunctionCall(value) => value.fmt(f),
+            Self::Func(value) => value.fmt(f),
             Self::Ident(value) => value.fmt(f),
             Self::Array(value) => value.fmt(f),
             Self::Object(value) => value.fmt(f),
@@ -132,24 +178,86 @@ impl fmt::Display for Expression {
             Self::Match(value) => value.fmt(f),
             Self::Binary(value) => value.fmt(f),
             Self::Index(value) => value.fmt(f),
+            Self::Range(value) => value.fmt(f),
+            Self::Unary(value) => value.fmt(f),
+            Self::Cast(value) => value.fmt(f),
         }
     }
 }
 
-impl Parse for Expression {
-    fn parse(parser: &mut Parser) -> ParseResult<Positioned<Self>> {
-        let value = Literal::parse(parser)
-            .map(|value| value.map(Self::Literal))
-            .or_else(|_| ObjectExpr::parse(parser).map(|value| value.map(Self::Object)))
-            .or_else(|_| ArrayExpr::parse(parser).map(|value| value.map(Self::Array)))
-            .or_else(|_| {
+impl Expression {
+    /// Parses expression grouped by parentheses, `(a + b)`.
+    fn parse_parenthesized(parser: &mut Parser) -> ParseResult<Positioned<Self>> {
+        let start = parser.consume(&Token::ParenOpen)?;
+        let value = Self::parse(parser)?;
+        let end = parser.consume(&Token::ParenClose)?;
+
+        Ok(start.between(&end).wrap(value.value))
+    }
+
+    /// Parses expression containing only operators which bind tighter than `precedence`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError` if parsing failed
+    pub fn parse_with_precedence(
+        parser: &mut Parser,
+        precedence: Precedence,
+    ) -> ParseResult<Positioned<Self>> {
+        let start = parser.peek().map(|token| token.span);
+
+        let position = parser.position();
+
+        // literal goes before unary expression, which would take sign of `-9223372036854775808`
+        let value = Self::parse_parenthesized(parser)
+            .or_parse(parser.position() != position, || {
+                Literal::parse(parser).map(|value| value.map(Self::Literal))
+            })
+            .or_parse(parser.position() != position, || {
+                UnaryExpression::parse(parser).map(|value| value.map(Box::new).map(Self::Unary))
+            })
+            .or_parse(parser.position() != position, || {
+                FormattedString::parse(parser).map(|value| value.map(Self::Formatted))
+            })
+            .or_parse(parser.position() != position, || {
+                ObjectExpr::parse(parser).map(|value| value.map(Self::Object))
+            })
+            .or_parse(parser.position() != position, || {
+                ArrayExpr::parse(parser).map(|value| value.map(Self::Array))
+            })
+            .or_parse(parser.position() != position, || {
                 NewInstanceExpression::parse(parser).map(|value| value.map(Self::NewInstance))
             })
-            .or_else(|_| MatchExpression::parse(parser).map(|value| value.map(Self::Match)))
-            .or_else(|_| IfElseExpression::parse(parser).map(|value| value.map(Self::IfElse)))
-            .or_else(|_| Block::parse(parser).map(|value| value.map(Self::Block)))
-            .or_else(|_| Ident::parse(parser).map(|value| value.map(Self::Ident)))?;
+            .or_parse(parser.position() != position, || {
+                FuncExpression::parse(parser).map(|value| value.map(Self::Func))
+            })
+            .or_parse(parser.position() != position, || {
+                MatchExpression::parse(parser).map(|value| value.map(Self::Match))
+            })
+            .or_parse(parser.position() != position, || {
+                IfElseExpression::parse(parser).map(|value| value.map(Self::IfElse))
+            })
+            .or_parse(parser.position() != position, || {
+                Block::parse(parser).map(|value| value.map(Self::Block))
+            })
+            .or_parse(parser.position() != position, || {
+                Ident::parse(parser).map(|value| value.map(Self::Ident))
+            })
+            .map_err(|error| {
+                // none of the alternatives got past the first token
+                if error.location() == start {
+                    parser.expected_token("expression")
+                } else {
+                    error
+                }
+            })?;
 
-        go_parse_pratt_expr(parser, Precedence::PLowest, value)
+        go_parse_pratt_expr(parser, precedence, value)
+    }
+}
+
+impl Parse for Expression {
+    fn parse(parser: &mut Parser) -> ParseResult<Positioned<Self>> {
+        Self::parse_with_precedence(parser, Precedence::PLowest)
     }
 }
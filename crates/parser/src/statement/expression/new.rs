@@ -80,12 +80,20 @@ impl fmt::Display for InstanceArgs {
 #[derive(Debug, Clone, PartialEq)]
 pub struct NewInstanceExpression {
     pub target: Positioned<Ident>,
+    /// Enum variant, `new Enum.Variant(...)`.
+    pub variant: Option<Positioned<Ident>>,
     pub args: Positioned<InstanceArgs>,
 }
 
 impl fmt::Display for NewInstanceExpression {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "new {}{}", self.target, self.args)
+        write!(f, "new {}", self.target)?;
+
+        if let Some(variant) = &self.variant {
+            write!(f, ".{variant}")?;
+        }
+
+        self.args.fmt(f)
     }
 }
 
@@ -94,9 +102,18 @@ impl Parse for NewInstanceExpression {
         let start = parser.consume(&Token::New)?;
 
         let target = Ident::parse(parser)?;
+        let variant = if parser.try_consume(&Token::Dot) {
+            Some(Ident::parse(parser)?)
+        } else {
+            None
+        };
 
         let args = InstanceArgs::parse(parser)?;
 
-        Ok(start.between(&args).wrap(Self { target, args }))
+        Ok(start.between(&args).wrap(Self {
+            target,
+            variant,
+            args,
+        }))
     }
 }
@@ -3,8 +3,8 @@ use std::fmt;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct ObjectProperty {
-    name: Positioned<Ident>,
-    value: Positioned<Expression>,
+    pub name: Positioned<Ident>,
+    pub value: Positioned<Expression>,
 }
 
 impl fmt::Display for ObjectProperty {
@@ -33,7 +33,7 @@ impl Parse for ObjectProperty {
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct ObjectExpr {
-    properties: Vec<Positioned<ObjectProperty>>,
+    pub properties: Vec<Positioned<ObjectProperty>>,
 }
 
 impl fmt::Display for ObjectExpr {
@@ -57,6 +57,8 @@ impl Parse for ObjectExpr {
         parser.verify(&Token::Pound)?;
         parser.verify2(&Token::BraceOpen)?;
 
+        let start = parser.consume(&Token::Pound)?;
+
         ObjectProperty::parse_separated_in(
             parser,
             &Token::Comma,
@@ -67,7 +69,7 @@ impl Parse for ObjectExpr {
             |Positioned {
                  value: properties,
                  span,
-             }| span.wrap(Self { properties }),
+             }| start.span.between(span).wrap(Self { properties }),
         )
     }
 }
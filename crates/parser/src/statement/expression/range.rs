@@ -0,0 +1,47 @@
+use crate::prelude::*;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeExpression {
+    pub start: Positioned<Expression>,
+    pub end: Positioned<Expression>,
+    pub inclusive: bool,
+}
+
+impl fmt::Display for RangeExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}",
+            self.start,
+            if self.inclusive { "..=" } else { ".." },
+            self.end
+        )
+    }
+}
+
+impl RangeExpression {
+    /// Parses end of range, containing only operators which bind tighter than `precedence`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError` if parsing failed
+    pub fn parse(
+        parser: &mut Parser,
+        start: Positioned<Expression>,
+        precedence: Precedence,
+    ) -> ParseResult<Positioned<Self>> {
+        let inclusive = parser
+            .consume_one_of(&[Token::DotDot, Token::DotDotEq])?
+            .value
+            == Token::DotDotEq;
+
+        let end = Expression::parse_with_precedence(parser, precedence)?;
+
+        Ok(start.between(&end).wrap(Self {
+            start,
+            end,
+            inclusive,
+        }))
+    }
+}
@@ -0,0 +1,52 @@
+use crate::prelude::*;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnaryOperator {
+    Not,    // !
+    Negate, // -
+}
+
+impl fmt::Display for UnaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Not => "!",
+            Self::Negate => "-",
+        })
+    }
+}
+
+/// Prefix operator applied to expression, `!flag` or `-value`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnaryExpression {
+    pub operator: Positioned<UnaryOperator>,
+    pub value: Positioned<Expression>,
+}
+
+impl fmt::Display for UnaryExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.value.value {
+            Expression::Binary(_) | Expression::Range(_) | Expression::Cast(_) => {
+                write!(f, "{}({})", self.operator, self.value)
+            }
+            _ => write!(f, "{}{}", self.operator, self.value),
+        }
+    }
+}
+
+impl Parse for UnaryExpression {
+    fn parse(parser: &mut Parser) -> ParseResult<Positioned<Self>> {
+        let operator = parser
+            .consume(&Token::Not)
+            .map(|value| value.wrap(UnaryOperator::Not))
+            .or_else(|_| {
+                parser
+                    .consume(&Token::Minus)
+                    .map(|value| value.wrap(UnaryOperator::Negate))
+            })?;
+
+        let value = Expression::parse_with_precedence(parser, Precedence::PPrefix)?;
+
+        Ok(operator.between(&value).wrap(Self { operator, value }))
+    }
+}
@@ -1,15 +1,19 @@
+mod enumeration;
 mod expression;
 mod for_loop;
 mod func;
+mod pattern;
 mod record;
 mod structure;
 mod variable;
 mod while_loop;
 
 pub use self::{
+    enumeration::{EnumStatement, EnumVariant, EnumVariantFields},
     expression::*,
     for_loop::ForStatement,
     func::{FuncArg, FuncStatement},
+    pattern::{FieldPattern, InstancePattern, Pattern, PatternFields},
     record::RecordStatement,
     structure::{StructField, StructStatement},
     variable::VariableStatement,
@@ -23,9 +27,14 @@ pub enum Statement {
     Variable(VariableStatement),
     Struct(StructStatement),
     Record(RecordStatement),
+    Enum(EnumStatement),
     Func(FuncStatement),
     ForIn(ForStatement),
     WhileLoop(WhileStatement),
+    Break,
+    Continue,
+    /// Exits the function, `return value;` or `return;`.
+    Return(Option<Positioned<Expression>>),
     Expression(Expression),
 }
 
@@ -35,24 +44,80 @@ impl fmt::Display for Statement {
             Self::Variable(value) => value.fmt(f),
             Self::Struct(value) => value.fmt(f),
             Self::Record(value) => value.fmt(f),
+            Self::Enum(value) => value.fmt(f),
             Self::Func(value) => value.fmt(f),
             Self::ForIn(value) => value.fmt(f),
             Self::WhileLoop(value) => value.fmt(f),
+            Self::Break => f.write_str("break"),
+            Self::Continue => f.write_str("continue"),
+            Self::Return(Some(value)) => write!(f, "return {value}"),
+            Self::Return(None) => f.write_str("return"),
             Self::Expression(value) => value.fmt(f),
         }
     }
 }
 
+impl Statement {
+    fn parse_return(parser: &mut Parser) -> ParseResult<Positioned<Self>> {
+        let start = parser.consume(&Token::Return)?;
+
+        if parser.check(&Token::Semi) || parser.check(&Token::BraceClose) {
+            return Ok(start.wrap(Self::Return(None)));
+        }
+
+        let value = Expression::parse(parser)?;
+
+        Ok(start.between(&value).wrap(Self::Return(Some(value))))
+    }
+}
+
 impl Parse for Statement {
     fn parse(parser: &mut Parser) -> ParseResult<Positioned<Self>> {
+        let start = parser.peek().map(|token| token.span);
+
+        let position = parser.position();
+
         let value = VariableStatement::parse(parser)
             .map(|value| value.map(Self::Variable))
-            .or_else(|_| StructStatement::parse(parser).map(|value| value.map(Self::Struct)))
-            .or_else(|_| RecordStatement::parse(parser).map(|value| value.map(Self::Record)))
-            .or_else(|_| FuncStatement::parse(parser).map(|value| value.map(Self::Func)))
-            .or_else(|_| ForStatement::parse(parser).map(|value| value.map(Self::ForIn)))
-            .or_else(|_| WhileStatement::parse(parser).map(|value| value.map(Self::WhileLoop)))
-            .or_else(|_| Expression::parse(parser).map(|value| value.map(Self::Expression)))?;
+            .or_parse(parser.position() != position, || {
+                StructStatement::parse(parser).map(|value| value.map(Self::Struct))
+            })
+            .or_parse(parser.position() != position, || {
+                RecordStatement::parse(parser).map(|value| value.map(Self::Record))
+            })
+            .or_parse(parser.position() != position, || {
+                EnumStatement::parse(parser).map(|value| value.map(Self::Enum))
+            })
+            .or_parse(parser.position() != position, || {
+                FuncStatement::parse(parser).map(|value| value.map(Self::Func))
+            })
+            .or_parse(parser.position() != position, || {
+                ForStatement::parse(parser).map(|value| value.map(Self::ForIn))
+            })
+            .or_parse(parser.position() != position, || {
+                WhileStatement::parse(parser).map(|value| value.map(Self::WhileLoop))
+            })
+            .or_parse(parser.position() != position, || {
+                parser
+                    .consume(&Token::Break)
+                    .map(|value| value.wrap(Self::Break))
+            })
+            .or_parse(parser.position() != position, || {
+                parser
+                    .consume(&Token::Continue)
+                    .map(|value| value.wrap(Self::Continue))
+            })
+            .or_parse(parser.position() != position, || Self::parse_return(parser))
+            .or_parse(parser.position() != position, || {
+                Expression::parse(parser).map(|value| value.map(Self::Expression))
+            })
+            .map_err(|error| {
+                if error.location() == start {
+                    parser.expected_token("statement")
+                } else {
+                    error
+                }
+            })?;
 
         Ok(value)
     }
@@ -0,0 +1,236 @@
+use crate::prelude::*;
+use std::fmt;
+
+/// Pattern of struct-like field, `field` or `field: pattern`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldPattern {
+    pub field: Positioned<Ident>,
+    pub pattern: Positioned<Pattern>,
+}
+
+impl fmt::Display for FieldPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if matches!(&self.pattern.value, Pattern::Binding(name) if name == &self.field.value) {
+            self.field.fmt(f)
+        } else {
+            write!(f, "{}: {}", self.field, self.pattern)
+        }
+    }
+}
+
+impl Parse for FieldPattern {
+    fn parse(parser: &mut Parser) -> ParseResult<Positioned<Self>> {
+        let field = Ident::parse(parser)?;
+
+        let pattern = if parser.try_consume(&Token::Colon) {
+            Pattern::parse(parser)?
+        } else {
+            field.clone().map(Pattern::Binding)
+        };
+
+        Ok(field.between(&pattern).wrap(Self { field, pattern }))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatternFields {
+    Record(Vec<Positioned<Pattern>>),
+    Struct(Vec<Positioned<FieldPattern>>),
+}
+
+impl fmt::Display for PatternFields {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Record(patterns) => write!(
+                f,
+                "({})",
+                patterns
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Self::Struct(fields) => write!(
+                f,
+                " {{ {} }}",
+                fields
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+impl Parse for PatternFields {
+    fn parse(parser: &mut Parser) -> ParseResult<Positioned<Self>> {
+        if parser.check(&Token::ParenOpen) {
+            Pattern::parse_separated_in(
+                parser,
+                &Token::Comma,
+                &Token::ParenOpen,
+                &Token::ParenClose,
+            )
+            .map(|value| value.map(Self::Record))
+        } else {
+            FieldPattern::parse_separated_in(
+                parser,
+                &Token::Comma,
+                &Token::BraceOpen,
+                &Token::BraceClose,
+            )
+            .map(|value| value.map(Self::Struct))
+        }
+    }
+}
+
+/// Pattern destructuring instance of record, struct or enum variant, `Human { name, age }` or
+/// `Shape.Circle(radius)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstancePattern {
+    pub target: Positioned<Ident>,
+    pub variant: Option<Positioned<Ident>>,
+    pub fields: Positioned<PatternFields>,
+}
+
+impl fmt::Display for InstancePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.target.fmt(f)?;
+
+        if let Some(variant) = &self.variant {
+            write!(f, ".{variant}")?;
+        }
+
+        self.fields.fmt(f)
+    }
+}
+
+impl Parse for InstancePattern {
+    fn parse(parser: &mut Parser) -> ParseResult<Positioned<Self>> {
+        let fields_start = |value: &Token| matches!(value, Token::ParenOpen | Token::BraceOpen);
+
+        parser.verify_if(Token::is_ident)?;
+
+        if !parser.check2_if(fields_start) {
+            parser.verify2(&Token::Dot)?;
+            parser.verify3_if(Token::is_ident)?;
+            parser.verify4_if(fields_start)?;
+        }
+
+        let target = Ident::parse(parser)?;
+
+        let variant = if parser.try_consume(&Token::Dot) {
+            Some(Ident::parse(parser)?)
+        } else {
+            None
+        };
+
+        let fields = PatternFields::parse(parser)?;
+
+        Ok(target.between(&fields).wrap(Self {
+            target,
+            variant,
+            fields,
+        }))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    Wildcard,
+    Binding(Ident),
+    Instance(Box<InstancePattern>),
+    /// Matches if any of patterns matches, `1 | 2`.
+    Or(Vec<Positioned<Self>>),
+    /// Literal, range or unit variant the value is compared with.
+    Value(Box<Expression>),
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Wildcard => f.write_str("_"),
+            Self::Binding(value) => value.fmt(f),
+            Self::Instance(value) => value.fmt(f),
+            Self::Or(patterns) => f.write_str(
+                &patterns
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" | "),
+            ),
+            Self::Value(value) => value.fmt(f),
+        }
+    }
+}
+
+impl Pattern {
+    fn parse_binding(parser: &mut Parser) -> ParseResult<Positioned<Self>> {
+        parser.verify_if(Token::is_ident)?;
+        parser.verify2_if(|value| {
+            matches!(
+                value,
+                Token::FatArrow
+                    | Token::Pipe
+                    | Token::Comma
+                    | Token::ParenClose
+                    | Token::BraceClose
+                    | Token::If
+                    | Token::Colon
+                    | Token::Eq
+            )
+        })?;
+
+        let name = Ident::parse(parser)?;
+
+        Ok(if name.value.0 == "_" {
+            name.span.wrap(Self::Wildcard)
+        } else {
+            name.map(Self::Binding)
+        })
+    }
+
+    /// Parses value pattern, `1`, `0..10` or `Color.Red`.
+    ///
+    /// `|` separates alternatives of the pattern, so only operators binding tighter than bit-or are
+    /// parsed in the value.
+    fn parse_value(parser: &mut Parser) -> ParseResult<Positioned<Self>> {
+        let mut value = Expression::parse_with_precedence(parser, Precedence::PBitOr)?;
+
+        if parser.check_if(|value| matches!(value, Token::DotDot | Token::DotDotEq)) {
+            value = RangeExpression::parse(parser, value, Precedence::PBitOr)?
+                .map(Box::new)
+                .map(Expression::Range);
+        }
+
+        Ok(value.map(Box::new).map(Self::Value))
+    }
+
+    fn parse_single(parser: &mut Parser) -> ParseResult<Positioned<Self>> {
+        InstancePattern::parse(parser)
+            .map(|value| value.map(Box::new).map(Self::Instance))
+            .or_else(|_| Self::parse_binding(parser))
+            .or_else(|_| Self::parse_value(parser))
+    }
+}
+
+impl Parse for Pattern {
+    fn parse(parser: &mut Parser) -> ParseResult<Positioned<Self>> {
+        let pattern = Self::parse_single(parser)?;
+
+        if !parser.check(&Token::Pipe) {
+            return Ok(pattern);
+        }
+
+        let mut patterns = vec![pattern];
+
+        while parser.try_consume(&Token::Pipe) {
+            patterns.push(Self::parse_single(parser)?);
+        }
+
+        Ok(patterns[0]
+            .between(&patterns[patterns.len() - 1])
+            .wrap(Self::Or(patterns)))
+    }
+}
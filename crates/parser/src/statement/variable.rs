@@ -4,7 +4,7 @@ use std::fmt;
 #[derive(Debug, Clone, PartialEq)]
 pub struct VariableStatement {
     pub mutable: Positioned<bool>,
-    pub name: Positioned<Ident>,
+    pub pattern: Positioned<Pattern>,
     pub ty: Option<Positioned<Type>>,
     pub value: Positioned<Expression>,
 }
@@ -12,7 +12,7 @@ pub struct VariableStatement {
 impl fmt::Display for VariableStatement {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str(if self.mutable.value { "let " } else { "const " })?;
-        f.write_str(&self.name.value)?;
+        self.pattern.fmt(f)?;
 
         if let Some(ty) = &self.ty {
             write!(f, ": {ty}")?;
@@ -31,7 +31,7 @@ impl Parse for VariableStatement {
         parser.verify2_if(Token::is_ident)?;
 
         let def = parser.consume_one_of(&[Token::Let, Token::Const])?;
-        let name = Ident::parse(parser)?;
+        let pattern = Pattern::parse(parser)?;
         let ty = if parser.try_consume(&Token::Colon) {
             Some(Type::parse(parser)?)
         } else {
@@ -46,7 +46,7 @@ impl Parse for VariableStatement {
 
         Ok(def.between(&end).wrap(Self {
             mutable: def.wrap(def.value == Token::Let),
-            name,
+            pattern,
             ty,
             value,
         }))
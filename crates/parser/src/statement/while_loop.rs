@@ -33,8 +33,6 @@ impl Parse for WhileStatement {
     fn parse(parser: &mut Parser) -> ParseResult<Positioned<Self>> {
         let start = parser.consume(&Token::While)?;
 
-        println!("{parser:#?}");
-
         let condition = Expression::parse(parser)?;
 
         let body = Block::parse(parser)?;
@@ -1,10 +1,12 @@
+#![allow(clippy::result_large_err)]
+
 pub mod prelude {
     pub use crate::{FunctionBuilder, Runtime, RuntimeError};
     pub use tapt_compiler::prelude::*;
 }
 
 use crate::prelude::*;
-use std::rc::Rc;
+use std::{error::Error, fmt, rc::Rc};
 
 pub struct FunctionBuilder {
     name: String,
@@ -33,7 +35,12 @@ impl FunctionBuilder {
         self
     }
 
-    pub fn build<O: Into<Value> + AsType, F: Fn(&VM, Args) -> O + 'static>(
+    /// Registers native function, `body` fails if arguments have unexpected types.
+    ///
+    /// # Panics
+    ///
+    /// Panics if runtime state is not a compiler
+    pub fn build<O: Into<Value> + AsType, F: Fn(&VM, Args) -> Result<O, VmErrorKind> + 'static>(
         self,
         runtime: &mut Runtime,
         body: F,
@@ -45,13 +52,13 @@ impl FunctionBuilder {
             .unwrap()
             .add_native_func(&self.name, self.args.clone(), Some(O::as_type()));
 
-        runtime
-            .vm
-            .frame_mut()
-            .set_slot(slot, self.build_value(body));
+        runtime.vm.set_global(slot, self.build_value(body));
     }
 
-    pub fn build_value<O: Into<Value> + AsType, F: Fn(&VM, Args) -> O + 'static>(
+    pub fn build_value<
+        O: Into<Value> + AsType,
+        F: Fn(&VM, Args) -> Result<O, VmErrorKind> + 'static,
+    >(
         self,
         body: F,
     ) -> Value {
@@ -61,14 +68,90 @@ impl FunctionBuilder {
                 args: self.args,
                 output: O::as_type(),
             },
-            func: Rc::new(move |vm, args| body(vm, args).into()),
+            func: Rc::new(move |vm, args| body(vm, args).map(Into::into)),
         }))
     }
 }
 
+#[derive(Debug)]
 pub enum RuntimeError {
-    ParseError(ParseError),
+    /// Every error found while parsing the code.
+    ParseError(Vec<ParseError>),
     CompileError(CompileError),
+    Runtime(VmError),
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ParseError(errors) => {
+                let messages = errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+                f.write_str(&messages.join("\n"))
+            }
+            Self::CompileError(err) => err.fmt(f),
+            Self::Runtime(err) => write!(f, "{err} at line {}", err.line + 1),
+        }
+    }
+}
+
+impl Error for RuntimeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::ParseError(errors) => errors.first().map(|err| err as _),
+            Self::CompileError(err) => Some(err),
+            Self::Runtime(err) => Some(err),
+        }
+    }
+}
+
+impl RuntimeError {
+    /// Describes the error with labels pointing into `source` it was produced from,
+    /// parse errors produce a diagnostic each.
+    #[must_use]
+    pub fn diagnostics(&self, source: &str) -> Vec<Diagnostic> {
+        match self {
+            Self::ParseError(errors) => errors
+                .iter()
+                .map(|err| {
+                    let diagnostic = Diagnostic::new(err.message());
+
+                    match err.location() {
+                        Some(span) => diagnostic.with_primary(span, ""),
+                        None => diagnostic,
+                    }
+                })
+                .collect(),
+            Self::CompileError(err) => vec![err.diagnostic()],
+            Self::Runtime(err) => {
+                let mut diagnostic = Diagnostic::new(err.to_string());
+
+                if let Some(span) = Span::of_line(source, err.line) {
+                    diagnostic = diagnostic.with_primary(span, "");
+                }
+
+                for frame in &err.trace {
+                    diagnostic = match Span::of_line(source, frame.line) {
+                        Some(span) => diagnostic
+                            .with_secondary(span, format!("in `{}` called here", frame.function)),
+                        None => diagnostic.with_note(format!("in `{}`", frame.function)),
+                    };
+                }
+
+                vec![diagnostic]
+            }
+        }
+    }
+
+    /// Renders the error with snippets of `source`, using ANSI colours if `color` is set.
+    #[must_use]
+    pub fn render(&self, source: &str, color: bool) -> String {
+        self.diagnostics(source)
+            .iter()
+            .map(|diagnostic| diagnostic.render(source, color))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
 }
 
 pub struct Runtime {
@@ -83,32 +166,56 @@ impl Runtime {
         }
     }
 
+    /// # Errors
+    ///
+    /// Returns error if parsing, compilation or execution failed
+    ///
+    /// # Panics
+    ///
+    /// Panics if runtime state is not a compiler
     pub fn run<T: AsRef<str>>(&mut self, code: T) -> Result<Value, RuntimeError> {
         let code = code.as_ref();
         let mut parser = Parser::new(Lexer::parse(code));
 
-        match Block::parse_statements_until(&mut parser, &Token::EOF) {
-            Ok((statements, return_statement)) => {
-                println!("{statements:#?}");
-
-                match self
-                    .vm
-                    .state
-                    .downcast_mut::<Compiler>()
-                    .unwrap()
-                    .compile(statements, return_statement)
-                {
-                    Ok(value) => {
-                        println!("{value:#?}");
-
-                        Ok(self.vm.interpret(&value))
-                    }
-                    Err(err) => Err(RuntimeError::CompileError(err)),
-                }
+        let (statements, return_statement) =
+            Block::parse_statements_until(&mut parser, &Token::EOF);
+        let errors = parser.take_errors();
+
+        if !errors.is_empty() {
+            return Err(RuntimeError::ParseError(errors));
+        }
+
+        println!("{statements:#?}");
+
+        match self
+            .vm
+            .state
+            .downcast_mut::<Compiler>()
+            .unwrap()
+            .compile(statements, return_statement)
+        {
+            Ok(value) => {
+                println!("{value:#?}");
+
+                self.vm.interpret(&value).map_err(|err| {
+                    self.vm.frames.truncate(1);
+                    self.vm.stack.clear();
+
+                    RuntimeError::Runtime(err)
+                })
             }
-            Err(err) => Err(RuntimeError::ParseError(err)),
+            Err(err) => Err(RuntimeError::CompileError(err)),
         }
     }
+
+    /// Returns warnings produced by compilation since the last call.
+    ///
+    /// # Panics
+    ///
+    /// Panics if runtime state is not a compiler
+    pub fn take_warnings(&mut self) -> Vec<CompileWarning> {
+        std::mem::take(&mut self.vm.state.downcast_mut::<Compiler>().unwrap().warnings)
+    }
 }
 
 impl Default for Runtime {
@@ -120,7 +227,8 @@ impl Default for Runtime {
 #[cfg(test)]
 mod tests {
     use crate::prelude::*;
-    
+    use std::{cell::Cell, rc::Rc};
+
     #[test]
     fn test_runtime() {
         let mut runtime = Runtime::new();
@@ -131,31 +239,1485 @@ mod tests {
                 .arg::<i64>()
                 .arg::<i64>()
                 .build_value(|_, mut value| {
-                    let a = value.get::<i64>();
-                    let b = value.get::<i64>();
+                    let a = value.get::<i64>()?;
+                    let b = value.get::<i64>()?;
 
-                    a + b
+                    Ok(a + b)
                 },)
         );
 
         FunctionBuilder::new("println")
             .any_arg()
             .build(&mut runtime, |_, mut value| {
-                let value = value.get::<Value>();
+                let value = value.get::<Value>()?;
 
                 println!("{value}");
+
+                Ok(())
             });
 
         FunctionBuilder::new("sum").arg::<i64>().arg::<i64>().build(
             &mut runtime,
             |_, mut value| {
-                let a = value.get::<i64>();
-                let b = value.get::<i64>();
+                let a = value.get::<i64>()?;
+                let b = value.get::<i64>()?;
 
-                a + b
+                Ok(a + b)
             },
         );
 
-        runtime.run("println(sum(20, 40))");
+        runtime.run("println(sum(20, 40))").unwrap();
+    }
+
+    #[test]
+    fn test_while_loop() {
+        let mut runtime = Runtime::new();
+
+        let value = runtime
+            .run(
+                "
+                let i = 0;
+                let total = 0;
+
+                while true {
+                    i = i + 1;
+
+                    if i == 3 {
+                        continue;
+                    };
+
+                    if i == 6 {
+                        break;
+                    };
+
+                    total = total + i;
+                }
+
+                total
+                ",
+            )
+            .unwrap();
+
+        assert_eq!(value, Value::Integer(12));
+
+        // failed compilation doesn't leave the loop and its scope open
+        assert!(matches!(
+            runtime.run("let unset = 1; while true { zzz; }"),
+            Err(RuntimeError::CompileError(
+                CompileError::VariableNotExist { .. }
+            ))
+        ));
+        assert!(matches!(
+            runtime.run("break;"),
+            Err(RuntimeError::CompileError(
+                CompileError::OutsideOfLoop { .. }
+            ))
+        ));
+        assert!(matches!(
+            runtime.run("unset"),
+            Err(RuntimeError::CompileError(
+                CompileError::VariableNotExist { .. }
+            ))
+        ));
+        assert_eq!(runtime.run("total").unwrap(), Value::Integer(12));
+    }
+
+    #[test]
+    fn test_for_loop() {
+        let mut runtime = Runtime::new();
+        let counter = Rc::new(Cell::new(3));
+
+        FunctionBuilder::new("countdown").build(&mut runtime, move |_, _| {
+            let value = counter.get();
+
+            counter.set(value - 1);
+
+            Ok((value > 0).then_some(value))
+        });
+
+        let value = runtime
+            .run(
+                "
+                let total = 0;
+
+                for i in 0..5 {
+                    total = total + i;
+                }
+
+                for character in \"héllo\" {
+                    total = total + 1;
+                }
+
+                for value in countdown {
+                    total = total + value;
+                }
+
+                total
+                ",
+            )
+            .unwrap();
+
+        assert_eq!(value, Value::Integer(21));
+    }
+
+    #[test]
+    fn test_range() {
+        let mut runtime = Runtime::new();
+
+        let value = runtime
+            .run(
+                "
+                let n = 3;
+                let range = 1..=n * 2;
+                let total = 0;
+
+                for i in range {
+                    total = total + i;
+                }
+
+                for i in n..n + 2 {
+                    total = total + i;
+                }
+
+                if range.contains(6) {
+                    total = total + range.len();
+                };
+
+                total + match total {
+                    0..10 => 0,
+                    10..=n * 20 => 100,
+                    _ => 1000,
+                }
+                ",
+            )
+            .unwrap();
+
+        assert_eq!(value, Value::Integer(134));
+
+        let value = runtime
+            .run(
+                "
+                let min = 0 - 9223372036854775807 - 1;
+                let count = 0;
+
+                for i in 9223372036854775806..=9223372036854775807 {
+                    count = count + 1;
+                }
+
+                [(min..9223372036854775807).len(), (min..=9223372036854775807).len(), count]
+                ",
+            )
+            .unwrap();
+
+        assert_eq!(
+            value,
+            Value::object(Object::Array(vec![
+                Value::Integer(i64::MAX),
+                Value::Integer(i64::MAX),
+                Value::Integer(2),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_array() {
+        let mut runtime = Runtime::new();
+
+        let value = runtime
+            .run(
+                "
+                let values: [int] = [1, 2, 3];
+                let nested = [[1], [2, 3]];
+                let total = 0;
+
+                values[1] = 20;
+
+                for value in values {
+                    total = total + value;
+                }
+
+                total + nested[1][0] * values.len()
+                ",
+            )
+            .unwrap();
+
+        assert_eq!(value, Value::Integer(30));
+
+        assert!(matches!(
+            runtime.run("[1, 2, 3][3]"),
+            Err(RuntimeError::Runtime(VmError {
+                kind: VmErrorKind::IndexOutOfBounds { index: 3, len: 3 },
+                ..
+            }))
+        ));
+        assert!(matches!(
+            runtime.run("[1, 2][true]"),
+            Err(RuntimeError::CompileError(
+                CompileError::TypeExpected { .. }
+            ))
+        ));
+        assert!(matches!(
+            runtime.run("let e = []; e = [1]; let arr: [[[int]]] = [e]; arr[0][0][0]"),
+            Err(RuntimeError::CompileError(
+                CompileError::TypeAnnotationRequired { .. }
+            ))
+        ));
+        assert!(matches!(
+            runtime.run("let arr: [[int]] = [[], [\"one\"]];"),
+            Err(RuntimeError::CompileError(
+                CompileError::TypeExpected { .. }
+            ))
+        ));
+        assert_eq!(
+            runtime
+                .run("let empty: [[int]] = [[], [1]]; empty = [[]]; empty.len()")
+                .unwrap(),
+            Value::Integer(1)
+        );
+    }
+
+    #[test]
+    fn test_object() {
+        let mut runtime = Runtime::new();
+
+        let value = runtime
+            .run(
+                "
+                struct Point { x: int, y: int }
+
+                let config = #{ port: 80, origin: #{ x: 1, y: 2 } };
+                let point = new Point { x: 0, y: 0 };
+
+                config.port = config.port + 8000;
+                point = config.origin;
+
+                config.port + point.y
+                ",
+            )
+            .unwrap();
+
+        assert_eq!(value, Value::Integer(8082));
+
+        // fields are matched by name, whatever order they are written in
+        assert_eq!(
+            runtime.run("point = #{ y: 1, x: 2 }; point.x").unwrap(),
+            Value::Integer(2)
+        );
+        assert_eq!(
+            runtime
+                .run(
+                    "
+                    struct Human { name: string, age: int }
+
+                    let human: Human = #{ age: 1, name: \"a\" };
+
+                    human.name + \" \" + human.age as string
+                    "
+                )
+                .unwrap(),
+            Value::from("a 1")
+        );
+        assert_eq!(
+            runtime
+                .run("#{ x: 1, y: #{ a: 2, b: 3 } } == #{ y: #{ b: 3, a: 2 }, x: 1 }")
+                .unwrap(),
+            Value::Boolean(true)
+        );
+
+        assert!(matches!(
+            runtime.run("point = #{ x: 1 }"),
+            Err(RuntimeError::CompileError(
+                CompileError::TypeExpected { .. }
+            ))
+        ));
+        assert!(matches!(
+            runtime.run("config.host"),
+            Err(RuntimeError::CompileError(
+                CompileError::PropertyNotExist { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_named_types() {
+        let mut runtime = Runtime::new();
+
+        let value = runtime
+            .run(
+                "
+                struct Human { name: string, age: int }
+                record Pair(Human, int?);
+
+                func older(human: Human, years: int): Human {
+                    human.age = human.age + years;
+                    human
+                };
+
+                let people: [Human] = [new Human { name: \"Tapt\", age: 1 }];
+                let pair = new Pair(older(people[0], 10), 3);
+
+                pair.0.age
+                ",
+            )
+            .unwrap();
+
+        assert_eq!(value, Value::Integer(11));
+
+        assert!(matches!(
+            runtime.run("func greet(alien: Alien) {}"),
+            Err(RuntimeError::CompileError(
+                CompileError::UnknownType { name, .. }
+            )) if name == "Alien"
+        ));
+    }
+
+    #[test]
+    fn test_declared_types() {
+        let mut runtime = Runtime::new();
+
+        let value = runtime
+            .run(
+                "
+                let value = 1;
+                let values: [int] = [];
+
+                if true {
+                    let value = \"shadowed\";
+                };
+
+                for i in 1..3 {
+                    let value: int = i * 10;
+
+                    values = [value];
+                }
+
+                value + values[0]
+                ",
+            )
+            .unwrap();
+
+        assert_eq!(value, Value::Integer(21));
+
+        // initializer reads the shadowed variable
+        assert_eq!(
+            runtime
+                .run("let x = 1; if true { let x = x + 1; x } else { 0 }")
+                .unwrap(),
+            Value::Integer(2)
+        );
+
+        assert!(matches!(
+            runtime.run("let number: float = 1;"),
+            Err(RuntimeError::CompileError(CompileError::TypeExpected {
+                expected: Type::Float,
+                found: Type::Integer,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_optionals() {
+        let mut runtime = Runtime::new();
+
+        let value = runtime
+            .run(
+                "
+                func parse(text: string): int {
+                    match text.parse_int() {
+                        none => 0,
+                        value => value * 2,
+                    }
+                };
+
+                let maybe: int? = 5;
+
+                parse(\"21\") + parse(\"x\") + match maybe { none => 0, 5 => 1, _ => 2 }
+                ",
+            )
+            .unwrap();
+
+        assert_eq!(value, Value::Integer(43));
+
+        for source in [
+            "let x: int = \"abc\".parse_int();",
+            "func f(): int? { none }; const x = f() + 1;",
+            "func f(x: int): int { x }; f(\"1\".parse_int())",
+            "func f(x: int?): int { x };",
+        ] {
+            assert!(
+                matches!(
+                    runtime.run(source),
+                    Err(RuntimeError::CompileError(
+                        CompileError::TypeExpected { .. } | CompileError::OneOfTypeExpected { .. }
+                    ))
+                ),
+                "{source}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_enum() {
+        let mut runtime = Runtime::new();
+
+        let value = runtime
+            .run(
+                "
+                enum Shape { Circle(float), Rect { w: float, h: float }, Empty }
+
+                func area(shape: Shape): float {
+                    match shape {
+                        Shape.Circle(r) => r * r * 3.0,
+                        Shape.Rect { w, h: height } => w * height,
+                        Shape.Empty => 0.0,
+                    }
+                };
+
+                let shapes = [new Shape.Circle(1.0), new Shape.Rect { h: 2.0, w: 3.0 }, Shape.Empty];
+                let total = 0.0;
+
+                for shape in shapes {
+                    total = total + area(shape);
+                }
+
+                total
+                ",
+            )
+            .unwrap();
+
+        assert_eq!(value, Value::Float(9.0));
+
+        assert!(matches!(
+            runtime.run(
+                "
+                enum Color { Red, Green }
+
+                Color.Blue
+                "
+            ),
+            Err(RuntimeError::CompileError(
+                CompileError::PropertyNotExist { .. }
+            ))
+        ));
+        assert!(matches!(
+            runtime.run("Shape.Circle(2.0)"),
+            Err(RuntimeError::CompileError(
+                CompileError::VariantWithoutNew { constructor, .. }
+            )) if constructor == "new Shape.Circle(...)"
+        ));
+    }
+
+    #[test]
+    fn test_match_exhaustiveness() {
+        let mut runtime = Runtime::new();
+
+        let value = runtime
+            .run(
+                "
+                enum Light { Red, Yellow, Green }
+
+                func next(light: Light): Light {
+                    match light {
+                        Light.Red => Light.Green,
+                        Light.Green => Light.Yellow,
+                        Light.Yellow => Light.Red,
+                    }
+                };
+
+                let value = match false {
+                    true => 1,
+                    false => 2,
+                    _ => 3,
+                };
+
+                match value {
+                    1 => {},
+                };
+
+                match next(Light.Red) {
+                    Light.Yellow => 10,
+                    other => value,
+                }
+                ",
+            )
+            .unwrap();
+
+        assert_eq!(value, Value::Integer(2));
+        assert!(matches!(
+            runtime.take_warnings().as_slice(),
+            [CompileWarning::UnreachablePattern { .. }]
+        ));
+
+        assert!(matches!(
+            runtime.run("match 3 { 1 => 10 }"),
+            Err(RuntimeError::CompileError(CompileError::NonExhaustiveMatch { missing, .. }))
+                if missing == ["_"]
+        ));
+
+        assert!(matches!(
+            runtime.run("match Light.Red { Light.Red => 1, Light.Green => 2 }"),
+            Err(RuntimeError::CompileError(CompileError::NonExhaustiveMatch { missing, .. }))
+                if missing == ["Yellow"]
+        ));
+    }
+
+    #[test]
+    fn test_patterns() {
+        let mut runtime = Runtime::new();
+
+        let value = runtime
+            .run(
+                "
+                record SimpleHuman(string, int, bool);
+                struct Human { name: string, age: int }
+
+                func score(human: SimpleHuman): int {
+                    match human {
+                        SimpleHuman(\"root\", _, _) => 1000,
+                        SimpleHuman(_, age, true) if age > 17 => age,
+                        SimpleHuman(_, 0 | 1, false) => 1,
+                        SimpleHuman(_, 2..10, _) => 2,
+                        _ => 0,
+                    }
+                };
+
+                let total = score(new SimpleHuman(\"root\", 1, false))
+                    + score(new SimpleHuman(\"adult\", 20, true))
+                    + score(new SimpleHuman(\"baby\", 1, false))
+                    + score(new SimpleHuman(\"child\", 5, true))
+                    + score(new SimpleHuman(\"old\", 50, false));
+
+                let Human { age, name: _ } = new Human { name: \"bob\", age: 30 };
+                const SimpleHuman(_, years, _) = new SimpleHuman(\"eve\", 7, true);
+
+                total + age + years
+                ",
+            )
+            .unwrap();
+
+        assert_eq!(value, Value::Integer(1060));
+
+        let globals = runtime.vm.globals.len();
+        let value = runtime
+            .run("let SimpleHuman(_, more, _) = new SimpleHuman(\"ann\", 3, true); more")
+            .unwrap();
+
+        assert_eq!(value, Value::Integer(3));
+        assert_eq!(runtime.vm.globals.len(), globals + 1);
+
+        assert!(matches!(
+            runtime.run("let SimpleHuman(_, 1, _) = new SimpleHuman(\"tom\", 1, true);"),
+            Err(RuntimeError::CompileError(
+                CompileError::RefutablePattern { .. }
+            ))
+        ));
+        assert!(matches!(
+            runtime.run("match 1 { 1 | value => 1, _ => 0 }"),
+            Err(RuntimeError::CompileError(
+                CompileError::InconsistentBinding { name, .. }
+            )) if name == "value"
+        ));
+    }
+
+    #[test]
+    fn test_closures() {
+        let mut runtime = Runtime::new();
+
+        let value = runtime
+            .run(
+                "
+                let offset = 10;
+                const add = func(x: int): int { x + offset };
+                offset = 20;
+
+                func counter(): func(): int {
+                    let count = 0;
+
+                    func(): int {
+                        count = count + 1;
+                        count
+                    }
+                };
+
+                const next = counter();
+                const other = counter();
+
+                next();
+                next();
+
+                func outer(): int {
+                    let value = 1;
+                    const inner = func(): int { func(): int { value * 100 }() };
+                    value = 5;
+                    inner()
+                };
+
+                [add(1), next(), other(), outer()]
+                ",
+            )
+            .unwrap();
+
+        assert_eq!(
+            value,
+            Value::object(Object::Array(vec![
+                Value::Integer(21),
+                Value::Integer(3),
+                Value::Integer(1),
+                Value::Integer(500),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_globals() {
+        let mut runtime = Runtime::new();
+
+        FunctionBuilder::new("double")
+            .arg::<i64>()
+            .build(&mut runtime, |_, mut value| Ok(value.get::<i64>()? * 2));
+
+        let value = runtime
+            .run(
+                "
+                struct Point { x: int, y: int }
+
+                func is_even(n: int): bool {
+                    if n == 0 { true } else { is_odd(n - 1) }
+                };
+
+                func is_odd(n: int): bool {
+                    if n == 0 { false } else { is_even(n - 1) }
+                };
+
+                let created = 0;
+
+                func point(x: int): Point {
+                    created = created + 1;
+                    new Point { x: double(x), y: 0 }
+                };
+
+                point(1);
+
+                if is_even(10) { if is_odd(7) { point(20).x + created } else { 0 } } else { 0 }
+                ",
+            )
+            .unwrap();
+
+        assert_eq!(value, Value::Integer(42));
+
+        let value = runtime
+            .run(
+                "
+                let first = second;
+                let age = older(new Human { name: \"Tapt\", age: 1 }).age;
+
+                func second(): int { 2 };
+                func older(human: Human): Human {
+                    human.age = human.age + count(Tree.Leaf);
+                    human
+                };
+                func count(tree: Tree): int {
+                    match tree { Tree.Leaf => 1, Tree.Node(pair) => pair.1 }
+                };
+
+                enum Tree { Leaf, Node(Pair) }
+                record Pair(Human, int);
+                struct Human { name: string, age: int }
+
+                first() + age
+                ",
+            )
+            .unwrap();
+
+        assert_eq!(value, Value::Integer(4));
+
+        assert!(matches!(
+            runtime.run("uninitialized(); let early = 1; func uninitialized(): int { early };"),
+            Err(RuntimeError::Runtime(VmError {
+                kind: VmErrorKind::UninitializedGlobal,
+                ..
+            }))
+        ));
+        assert!(matches!(
+            runtime.run("struct Node { value: int, next: Node? }"),
+            Err(RuntimeError::CompileError(
+                CompileError::RecursiveType { name, .. }
+            )) if name == "Node"
+        ));
+        assert!(matches!(
+            runtime.run("struct A { b: B }; record B(C?); enum C { Some(A), None }"),
+            Err(RuntimeError::CompileError(
+                CompileError::RecursiveType { name, through, .. }
+            )) if name == "A" && through == ["B", "C"]
+        ));
+
+        for source in [
+            "func f(): [int] { [1] }; let a = f()[0]; func f(): int { 1 }; a",
+            "let g = 5; func g(): int { 1 };",
+            "func h(): int { 1 }; struct h { x: int }",
+        ] {
+            assert!(matches!(
+                runtime.run(source),
+                Err(RuntimeError::CompileError(
+                    CompileError::AlreadyDeclared { .. }
+                ))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_return() {
+        let mut runtime = Runtime::new();
+
+        let value = runtime
+            .run(
+                "
+                func find(values: [int], target: int): int {
+                    let index = 0;
+
+                    for value in values {
+                        if value == target {
+                            return index;
+                        };
+
+                        index = index + 1;
+                    }
+
+                    100
+                };
+
+                func classify(x: int): int {
+                    match x {
+                        0 => { return 1000; },
+                        _ => { { return x; } },
+                    }
+                };
+
+                find([4, 5, 6], 6) + find([1], 9) + classify(0) + classify(7)
+                ",
+            )
+            .unwrap();
+
+        assert_eq!(value, Value::Integer(1109));
+
+        let value = runtime
+            .run(
+                "
+                func first(): int? { none };
+                func second(): int? { return none; };
+                const third = func(): int? { none };
+
+                let a: int? = first();
+                let b: int? = second();
+                let c: int? = third();
+
+                [a == none, b == none, c == none]
+                ",
+            )
+            .unwrap();
+
+        assert_eq!(
+            value,
+            Value::object(Object::Array(vec![Value::Boolean(true); 3]))
+        );
+
+        assert!(matches!(
+            runtime.run("func check(x: int): int { if x > 0 { return 1; } };"),
+            Err(RuntimeError::CompileError(
+                CompileError::MissingReturn { .. }
+            ))
+        ));
+        assert!(matches!(
+            runtime.run("func name(): string { return 1; };"),
+            Err(RuntimeError::CompileError(
+                CompileError::TypeExpected { .. }
+            ))
+        ));
+        assert!(matches!(
+            runtime.run("return 1;"),
+            Err(RuntimeError::CompileError(
+                CompileError::ReturnOutsideOfFunction { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_unary() {
+        let mut runtime = Runtime::new();
+
+        let value = runtime
+            .run(
+                "
+                let a = 5;
+                const flag = false;
+
+                func negate(x: int): int { -x };
+
+                const values = [a -1, -a * 2, -negate(4) - -1, -[1, 2, 3][1]];
+                let total = 0;
+
+                for value in values {
+                    total = total + value;
+                }
+
+                if !flag { total } else { 0 }
+                ",
+            )
+            .unwrap();
+
+        assert_eq!(value, Value::Integer(-3));
+
+        assert!(matches!(
+            runtime.run("!1"),
+            Err(RuntimeError::CompileError(
+                CompileError::TypeExpected { .. }
+            ))
+        ));
+        assert!(matches!(
+            runtime.run("-true"),
+            Err(RuntimeError::CompileError(
+                CompileError::OneOfTypeExpected { .. }
+            ))
+        ));
+
+        assert_eq!(
+            runtime.run("-9223372036854775808").unwrap(),
+            Value::Integer(i64::MIN)
+        );
+        assert_eq!(
+            runtime
+                .run("a + -9223372036854775808 == -9223372036854775803")
+                .unwrap(),
+            Value::Boolean(true)
+        );
+
+        for source in [
+            "9223372036854775808",
+            "-9223372036854775808.abs()",
+            "a - 99999999999999999999",
+        ] {
+            assert!(
+                matches!(
+                    runtime.run(source),
+                    Err(RuntimeError::ParseError(errors))
+                        if errors[0].message() == "Integer literal doesn't fit into `int`"
+                ),
+                "{source}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_precedence() {
+        let mut runtime = Runtime::new();
+
+        for (source, expected) in [
+            ("1 - 2 - 3", Value::Integer(-4)),
+            ("100 / 10 / 5", Value::Integer(2)),
+            ("2 + 3 * 4", Value::Integer(14)),
+            ("2 * 3 + 4", Value::Integer(10)),
+            ("(2 + 3) * 4", Value::Integer(20)),
+            ("2 * (3 + 4) * 5", Value::Integer(70)),
+            ("10 - (4 - 3)", Value::Integer(9)),
+            ("-(3 + 2) * 2", Value::Integer(-10)),
+            ("((1))", Value::Integer(1)),
+            ("1 + 2 == 3", Value::Boolean(true)),
+            ("1 < 2 == 3 > 4", Value::Boolean(false)),
+            ("false && true || true", Value::Boolean(true)),
+            ("true || false && false", Value::Boolean(true)),
+            ("(true || false) && false", Value::Boolean(false)),
+            ("1 + 1 == 2 && 2 * 2 == 4", Value::Boolean(true)),
+            ("!(1 == 2)", Value::Boolean(true)),
+        ] {
+            assert_eq!(runtime.run(source).unwrap(), expected, "{source}");
+        }
+
+        let value = runtime
+            .run(
+                "
+                let a = 2;
+                let equal = false;
+
+                equal = a + 1 == 3;
+
+                match 5 { 1 | 2 => 0, 0..a * 3 => 1, _ => 2 } + if equal { 10 } else { 0 }
+                ",
+            )
+            .unwrap();
+
+        assert_eq!(value, Value::Integer(11));
+
+        for source in [
+            "let a = 0; let b = 0; a = b = 3; a",
+            "let c = 0; let d = 0; d += c = 1; d",
+        ] {
+            assert!(
+                matches!(
+                    runtime.run(source),
+                    Err(RuntimeError::CompileError(
+                        CompileError::ChainedAssignment { .. }
+                    ))
+                ),
+                "{source}"
+            );
+        }
+
+        assert!(matches!(
+            runtime.run("func f(value: int) {}; let e = 0; f(e = 1)"),
+            Err(RuntimeError::CompileError(
+                CompileError::TypeExpected { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_operators() {
+        let mut runtime = Runtime::new();
+
+        for (source, expected) in [
+            ("13 % 5", Value::Integer(3)),
+            ("7.5 % 2.0 == 1.5", Value::Boolean(true)),
+            ("13 & 6", Value::Integer(4)),
+            ("13 | 2", Value::Integer(15)),
+            ("13 ^ 1", Value::Integer(12)),
+            ("1 << 4", Value::Integer(16)),
+            ("13 >> 2", Value::Integer(3)),
+            ("1 | 2 & 3", Value::Integer(3)),
+            ("1 + 1 << 2", Value::Integer(8)),
+            ("3 <= 3", Value::Boolean(true)),
+            ("2.5 >= 3.0", Value::Boolean(false)),
+            ("2 >= 2", Value::Boolean(true)),
+            ("0.0 / 0.0 <= 1.0", Value::Boolean(false)),
+            ("1.0 >= 0.0 / 0.0", Value::Boolean(false)),
+            ("1 | 2 == 3", Value::Boolean(true)),
+            ("match 2 { 1 | 2 => 10, _ => 0 }", Value::Integer(10)),
+        ] {
+            assert_eq!(runtime.run(source).unwrap(), expected, "{source}");
+        }
+
+        assert!(matches!(
+            runtime.run("const value = 1.0 & 2.0;"),
+            Err(RuntimeError::CompileError(
+                CompileError::TypeExpected { .. }
+            ))
+        ));
+        assert!(matches!(
+            runtime.run("const value = true % false;"),
+            Err(RuntimeError::CompileError(
+                CompileError::OneOfTypeExpected { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_compound_assignment() {
+        let mut runtime = Runtime::new();
+
+        let value = runtime
+            .run(
+                "
+                struct Human { name: string, age: int };
+
+                func older(human: Human): Human {
+                    human.age += 10;
+                    human
+                };
+
+                let me = new Human { name: \"me\", age: 20 };
+
+                me.age += 1;
+                me.age *= 2;
+                older(me).age -= 2;
+
+                let values = [1, 2, 3];
+                let total = 100;
+
+                values[1] -= 10;
+                total /= 4;
+
+                for value in values {
+                    total += value;
+                }
+
+                [me.age, values[1], total]
+                ",
+            )
+            .unwrap();
+
+        assert_eq!(
+            value,
+            Value::object(Object::Array(vec![
+                Value::Integer(50),
+                Value::Integer(-8),
+                Value::Integer(21),
+            ]))
+        );
+
+        for source in [
+            "const value = 1; value += 1;",
+            "const me = new Human { name: \"me\", age: 1 }; me.age += 1;",
+            "const values = [[1]]; values[0][0] = 2;",
+        ] {
+            assert!(matches!(
+                runtime.run(source),
+                Err(RuntimeError::CompileError(
+                    CompileError::ImmutableVariable { .. }
+                ))
+            ));
+        }
+        assert!(matches!(
+            runtime.run("let value = 1; value += 1.5;"),
+            Err(RuntimeError::CompileError(
+                CompileError::TypeExpected { .. }
+            ))
+        ));
+        assert!(matches!(
+            runtime.run("let value = true; value -= false;"),
+            Err(RuntimeError::CompileError(
+                CompileError::OneOfTypeExpected { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_formatted_string() {
+        let mut runtime = Runtime::new();
+
+        let value = runtime
+            .run(
+                "
+                struct Human { name: string, age: int };
+
+                const me = new Human { name: \"me\", age: 20 };
+                const items = [1, 2];
+
+                \"{me.name} is {me.age + 1}, has {items} and {true}\"
+                ",
+            )
+            .unwrap();
+
+        assert_eq!(value, Value::from("me is 21, has [1, 2] and true"));
+
+        let Err(RuntimeError::CompileError(CompileError::VariableNotExist { accessed_at, .. })) =
+            runtime.run("const name = \"me\"; \"hi {nmae}\"")
+        else {
+            panic!("expected unknown variable");
+        };
+
+        assert_eq!((accessed_at.start, accessed_at.end), (24, 28));
+    }
+
+    #[test]
+    fn test_strings() {
+        let mut runtime = Runtime::new();
+
+        let value = runtime
+            .run(
+                "
+                let greeting = \"  Hello, World  \".trim();
+
+                greeting += \"!\";
+
+                [
+                    greeting.to_upper(),
+                    greeting.substring(0..5) + greeting.substring(12..=20),
+                    greeting.replace(\"World\", \"tapt\"),
+                    \"{greeting.len()} {greeting.chars().len()} {\"a,b\".split(\",\")}\",
+                ]
+                ",
+            )
+            .unwrap();
+
+        assert_eq!(
+            value,
+            Value::object(Object::Array(vec![
+                Value::from("HELLO, WORLD!"),
+                Value::from("Hello!"),
+                Value::from("Hello, tapt!"),
+                Value::from("13 13 [\"a\", \"b\"]"),
+            ]))
+        );
+
+        for (source, expected) in [
+            ("\"abc\" < \"abd\"", true),
+            ("\"b\" > \"abc\"", true),
+            ("\"a\" >= \"b\"", false),
+            ("\"tapt\".contains(\"ap\")", true),
+            ("\"tapt\".starts_with(\"ap\")", false),
+        ] {
+            assert_eq!(
+                runtime.run(source).unwrap(),
+                Value::Boolean(expected),
+                "{source}"
+            );
+        }
+
+        assert!(matches!(
+            runtime.run("const value = \"a\" - \"b\";"),
+            Err(RuntimeError::CompileError(
+                CompileError::OneOfTypeExpected { .. }
+            ))
+        ));
+
+        assert_eq!(
+            runtime
+                .run("\"x\".substring(0..=9223372036854775807)")
+                .unwrap(),
+            Value::from("x")
+        );
+
+        for source in [
+            "\"abc\".nope()",
+            "let x = 2; x.wrapping_sub(1)",
+            "(1..3).nope()",
+            "[1].foo",
+            "record Pair(int, int); new Pair(1, 2).2",
+        ] {
+            assert!(
+                matches!(
+                    runtime.run(source),
+                    Err(RuntimeError::CompileError(
+                        CompileError::PropertyNotExist { .. }
+                    ))
+                ),
+                "{source}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_casts() {
+        let mut runtime = Runtime::new();
+
+        for (source, expected) in [
+            ("7 as float / 2.0", Value::Float(3.5)),
+            ("-2.9 as int", Value::Integer(-2)),
+            ("(3 + 4) as float", Value::Float(7.0)),
+            (
+                "100000000000000000000000.0 as int",
+                Value::Integer(i64::MAX),
+            ),
+            ("true as int + false as int", Value::Integer(1)),
+            ("2.5 as string + 3 as string", Value::from("2.53")),
+            ("\"42\".parse_int()", Value::Integer(42)),
+            ("\"4x2\".parse_int()", Value::None),
+            ("\" 2.5 \".parse_float()", Value::Float(2.5)),
+            ("\"x\".parse_float() == none", Value::Boolean(true)),
+        ] {
+            assert_eq!(runtime.run(source).unwrap(), expected, "{source}");
+        }
+
+        assert!(matches!(
+            runtime.run("const value = \"1\" as int;"),
+            Err(RuntimeError::CompileError(CompileError::InvalidCast {
+                from: Type::String,
+                to: Type::Integer,
+                ..
+            }))
+        ));
+        assert!(matches!(
+            runtime.run("const value = 1 as bool;"),
+            Err(RuntimeError::CompileError(CompileError::InvalidCast { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_float_precision() {
+        let mut runtime = Runtime::new();
+
+        FunctionBuilder::new("half")
+            .arg::<f32>()
+            .build(&mut runtime, |_, mut value| Ok(value.get::<f32>()? / 2.0));
+
+        FunctionBuilder::new("twice")
+            .arg::<f64>()
+            .build(&mut runtime, |_, mut value| Ok(value.get::<f64>()? * 2.0));
+
+        let value = runtime
+            .run(
+                "
+                let total = 0.0;
+
+                for _ in 0..10 {
+                    total += 0.1;
+                }
+
+                [total, 16777216.0 + 1.0, twice(0.1), half(3.0)]
+                ",
+            )
+            .unwrap();
+
+        assert_eq!(
+            value,
+            Value::object(Object::Array(vec![
+                Value::Float(0.999_999_999_999_999_9),
+                Value::Float(16_777_217.0),
+                Value::Float(0.2),
+                Value::Float(1.5),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_checked_arithmetic() {
+        let mut runtime = Runtime::new();
+
+        let value = runtime
+            .run(
+                "
+                const max = 9223372036854775807;
+
+                [max.wrapping_add(1), max.saturating_add(1), (-max).saturating_add(-2)]
+                ",
+            )
+            .unwrap();
+
+        assert_eq!(
+            value,
+            Value::object(Object::Array(vec![
+                Value::Integer(i64::MIN),
+                Value::Integer(i64::MAX),
+                Value::Integer(i64::MIN),
+            ]))
+        );
+
+        for source in [
+            "9223372036854775807 + 1",
+            "-9223372036854775807 - 2",
+            "4611686018427387904 * 2",
+            "let value = -9223372036854775807 - 1; -value",
+            "let value = -9223372036854775807 - 1; value / -1",
+            "1 << 64",
+            "1 >> -1",
+        ] {
+            assert!(
+                matches!(
+                    runtime.run(source),
+                    Err(RuntimeError::Runtime(VmError {
+                        kind: VmErrorKind::IntegerOverflow,
+                        ..
+                    }))
+                ),
+                "{source}"
+            );
+        }
+
+        assert!(matches!(
+            runtime.run("let value = 0;\n\n10 % value"),
+            Err(RuntimeError::Runtime(VmError {
+                kind: VmErrorKind::DivisionByZero,
+                line: 2,
+                ..
+            }))
+        ));
+        assert_eq!(
+            runtime.run("1.0 / 0.0").unwrap(),
+            Value::Float(f64::INFINITY)
+        );
+    }
+
+    #[test]
+    fn test_runtime_errors() {
+        let mut runtime = Runtime::new();
+
+        let error = runtime.run(
+            "
+            func divide(value: int, by: int): int {
+                value / by
+            };
+
+            func average(values: [int]): int {
+                let sum = 0;
+
+                for value in values {
+                    sum += value;
+                }
+
+                divide(sum, values.len())
+            };
+
+            average([])
+            ",
+        );
+
+        let Err(RuntimeError::Runtime(error)) = error else {
+            panic!("expected runtime error, got {error:?}");
+        };
+
+        assert_eq!(error.kind, VmErrorKind::DivisionByZero);
+        assert_eq!(error.line, 2);
+        assert_eq!(
+            error.trace,
+            vec![
+                TraceFrame {
+                    function: "divide".into(),
+                    line: 12,
+                },
+                TraceFrame {
+                    function: "average".into(),
+                    line: 15,
+                },
+            ]
+        );
+
+        // the runtime stays usable after the error
+        assert_eq!(
+            runtime.run("average([1, 2, 3])").unwrap(),
+            Value::Integer(2)
+        );
+
+        FunctionBuilder::new("increment")
+            .any_arg()
+            .build(&mut runtime, |_, mut args| Ok(args.get::<i64>()? + 1));
+
+        assert!(matches!(
+            runtime.run("increment(\"one\")"),
+            Err(RuntimeError::Runtime(VmError {
+                kind: VmErrorKind::TypeMismatch {
+                    expected: Type::Integer,
+                    ..
+                },
+                ..
+            }))
+        ));
+
+        let Err(RuntimeError::Runtime(error)) =
+            runtime.run("func f(n: int): int { return f(n + 1); }; f(0)")
+        else {
+            panic!("expected stack overflow");
+        };
+
+        assert_eq!(error.kind, VmErrorKind::StackOverflow);
+        assert_eq!(error.trace.len(), MAX_CALL_DEPTH);
+        assert_eq!(runtime.run("average([2, 4])").unwrap(), Value::Integer(3));
+
+        let mut chunk = Chunk::new();
+
+        chunk.push(0, OpCode::Pop);
+
+        assert_eq!(
+            VM::new(()).interpret(&chunk).unwrap_err().kind,
+            VmErrorKind::StackUnderflow
+        );
+
+        let mut chunk = Chunk::new();
+
+        chunk.push(0, OpCode::GetLocal(0, 3));
+
+        assert_eq!(
+            VM::new(()).interpret(&chunk).unwrap_err().kind,
+            VmErrorKind::InvalidVariable
+        );
+
+        let mut chunk = Chunk::new();
+
+        chunk.push_const(Value::Integer(1));
+        chunk.push(0, OpCode::LoadConst(0));
+        chunk.push(0, OpCode::LoadConst(0));
+        chunk.push(0, OpCode::GetIndex);
+
+        assert_eq!(
+            VM::new(()).interpret(&chunk).unwrap_err().kind,
+            VmErrorKind::UnexpectedValue {
+                expected: "array",
+                found: "1".into()
+            }
+        );
+        assert_eq!(
+            Cast::IntToFloat.apply(Value::from("1")),
+            Err(VmErrorKind::InvalidCastOperand("\"1\"".into()))
+        );
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let mut runtime = Runtime::new();
+
+        let source = "const limit = 10;\nlimit = 20;";
+        let error = runtime.run(source).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "cannot assign twice to immutable variable `limit`"
+        );
+        assert_eq!(
+            error.render(source, false),
+            "\
+error: cannot assign twice to immutable variable `limit`
+ --> line 2:1
+  |
+1 | const limit = 10;
+  | ----------------- first assignment
+  |
+2 | limit = 20;
+  | ^^^^^ cannot assign twice
+  |
+  = note: declare the variable with `let` to make it mutable
+"
+        );
+
+        let source = "func divide(value: int, by: int): int {\n    value / by\n};\n\ndivide(1, 0)";
+        let error = runtime.run(source).unwrap_err();
+
+        assert_eq!(error.to_string(), "division by zero at line 2");
+        assert_eq!(
+            error.render(source, false),
+            "\
+error: division by zero
+ --> line 2:5
+  |
+2 |     value / by
+  |     ^^^^^^^^^^
+  |
+5 | divide(1, 0)
+  | ------------ in `divide` called here
+"
+        );
+    }
+
+    #[test]
+    fn test_parse_recovery() {
+        let mut runtime = Runtime::new();
+
+        let source = "
+            let a = ;
+            func half(x: int): int { let y = x +; y };
+            ) + 1;
+            let b = (1;
+            a + b
+        ";
+
+        let Err(RuntimeError::ParseError(errors)) = runtime.run(source) else {
+            panic!("expected parse errors");
+        };
+
+        let errors = errors
+            .iter()
+            .map(|error| {
+                let span = error.location().unwrap();
+
+                (error.message(), &source[span.start..span.end])
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            errors,
+            vec![
+                ("Expected expression, found ;", ";"),
+                ("Expected expression, found ;", ";"),
+                ("Expected statement, found )", ")"),
+                ("Expected ), found ;", ";"),
+            ]
+        );
+
+        assert_eq!(runtime.run("let a = 1; a + 1").unwrap(), Value::Integer(2));
+
+        // the failed declaration isn't parsed again as an expression
+        let Err(RuntimeError::ParseError(errors)) = runtime.run("func f( { }; 1") else {
+            panic!("expected parse errors");
+        };
+
+        assert_eq!(errors[0].message(), "Unexpected {");
     }
 }
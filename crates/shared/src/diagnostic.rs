@@ -0,0 +1,180 @@
+use std::fmt::Write;
+
+use crate::Span;
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// Part of the source highlighted by diagnostic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+    /// Primary labels point at the cause of the error, secondary ones give context.
+    pub primary: bool,
+}
+
+/// Error message with labelled snippets of the source it refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new<T: Into<String>>(message: T) -> Self {
+        Self {
+            message: message.into(),
+            labels: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_primary<T: Into<String>>(self, span: Span, message: T) -> Self {
+        self.with_label(span, message, true)
+    }
+
+    #[must_use]
+    pub fn with_secondary<T: Into<String>>(self, span: Span, message: T) -> Self {
+        self.with_label(span, message, false)
+    }
+
+    #[must_use]
+    pub fn with_note<T: Into<String>>(mut self, note: T) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    fn with_label<T: Into<String>>(mut self, span: Span, message: T, primary: bool) -> Self {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
+            primary,
+        });
+        self
+    }
+
+    /// Renders diagnostic with snippets of `source`, using ANSI colours if `color` is set.
+    #[must_use]
+    pub fn render(&self, source: &str, color: bool) -> String {
+        let paint = |style: &str, text: &str| {
+            if color {
+                format!("{style}{text}{RESET}")
+            } else {
+                text.to_string()
+            }
+        };
+
+        let mut lines = self
+            .labels
+            .iter()
+            .map(|label| (Location::find(source, label.span), label))
+            .collect::<Vec<_>>();
+
+        lines.sort_by_key(|(location, label)| (location.line, location.column, !label.primary));
+
+        let width = lines
+            .last()
+            .map_or(1, |(location, _)| (location.line + 1).to_string().len());
+        let gutter = paint(BLUE, &format!("{:width$} |", ""));
+
+        let mut output = format!(
+            "{}{}\n",
+            paint(RED, "error"),
+            paint(BOLD, &format!(": {}", self.message))
+        );
+
+        if let Some((location, _)) = lines
+            .iter()
+            .find(|(_, label)| label.primary)
+            .or_else(|| lines.first())
+        {
+            let _ = writeln!(
+                output,
+                "{:width$}{} line {}:{}",
+                "",
+                paint(BLUE, "-->"),
+                location.line + 1,
+                location.column + 1
+            );
+        }
+
+        let mut previous = None;
+
+        for (location, label) in &lines {
+            if previous != Some(location.line) {
+                let number = paint(BLUE, &format!("{:>width$} |", location.line + 1));
+
+                let _ = writeln!(output, "{gutter}");
+                let _ = writeln!(output, "{number} {}", location.text);
+
+                previous = Some(location.line);
+            }
+
+            let (style, marker) = if label.primary {
+                (RED, "^")
+            } else {
+                (BLUE, "-")
+            };
+            let markers = marker.repeat(location.len.max(1));
+
+            let _ = writeln!(
+                output,
+                "{gutter} {:column$}{}",
+                "",
+                paint(style, format!("{markers} {}", label.message).trim_end()),
+                column = location.column
+            );
+        }
+
+        if !lines.is_empty() && !self.notes.is_empty() {
+            let _ = writeln!(output, "{gutter}");
+        }
+
+        for note in &self.notes {
+            let _ = writeln!(output, "{:width$} {} {note}", "", paint(BOLD, "= note:"));
+        }
+
+        output
+    }
+}
+
+/// Position of span in the source, measured in characters.
+struct Location<'a> {
+    line: usize,
+    column: usize,
+    len: usize,
+    /// Source line containing the start of the span.
+    text: &'a str,
+}
+
+impl<'a> Location<'a> {
+    fn find(source: &'a str, span: Span) -> Self {
+        let start = floor_char_boundary(source, span.start);
+        let end = floor_char_boundary(source, span.end.max(start));
+
+        let line_start = source[..start].rfind('\n').map_or(0, |index| index + 1);
+        let line_end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |index| start + index);
+
+        Self {
+            line: source[..start].matches('\n').count(),
+            column: source[line_start..start].chars().count(),
+            // spans covering several lines are marked up to the end of the first one
+            len: source[start..end.min(line_end)].chars().count(),
+            text: source[line_start..line_end].trim_end(),
+        }
+    }
+}
+
+fn floor_char_boundary(source: &str, index: usize) -> usize {
+    (0..=index.min(source.len()))
+        .rev()
+        .find(|index| source.is_char_boundary(*index))
+        .unwrap_or(0)
+}
@@ -1,3 +1,7 @@
+mod diagnostic;
+
+pub use self::diagnostic::{Diagnostic, Label};
+
 use std::fmt;
 
 #[derive(Debug, Hash, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -29,6 +33,25 @@ impl Span {
         }
     }
 
+    /// Returns span of `line` in `source` without surrounding whitespace.
+    #[must_use]
+    pub fn of_line(source: &str, line: usize) -> Option<Self> {
+        let mut start = 0;
+
+        for (index, text) in source.split('\n').enumerate() {
+            if index == line {
+                let indent = text.len() - text.trim_start().len();
+                let start = start + indent;
+
+                return Some(Self::new(start, start + text.trim().len(), line, indent));
+            }
+
+            start += text.len() + 1;
+        }
+
+        None
+    }
+
     pub const fn wrap<A>(self, value: A) -> Positioned<A> {
         Positioned { value, span: self }
     }
@@ -1,4 +1,5 @@
 use derive_more::derive::Display;
+use std::fmt;
 
 #[derive(Debug, Display, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Type {
@@ -14,12 +15,25 @@ pub enum Type {
     Boolean,
     #[display("string")]
     String,
+    #[display("range")]
+    Range,
+    #[display("{_0}?")]
+    Optional(Box<Self>),
+    #[display("[{_0}]")]
+    Array(Box<Self>),
     #[display("func")]
     Function(FunctionType),
     #[display("record")]
     Record(RecordType),
     #[display("struct")]
     Struct(StructType),
+    #[display("{}", _0.name)]
+    Enum(EnumType),
+    #[display("{_0}")]
+    Object(ObjectType),
+    /// Name of user-defined type, resolved by compiler.
+    #[display("{_0}")]
+    Named(String),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -34,12 +48,71 @@ pub struct RecordType {
     pub fields: Vec<Type>,
 }
 
+/// Type declared with `struct`, fields are sorted by name like the ones of `ObjectType`.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct StructType {
     pub name: String,
     pub fields: Vec<(String, Type)>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum VariantFields {
+    Unit,
+    Tuple(Vec<Type>),
+    Struct(Vec<(String, Type)>),
+}
+
+impl VariantFields {
+    /// Returns types of the payload in the order it is stored.
+    #[must_use]
+    pub fn types(&self) -> Vec<Type> {
+        match self {
+            Self::Unit => Vec::new(),
+            Self::Tuple(fields) => fields.clone(),
+            Self::Struct(fields) => fields.iter().map(|(_, ty)| ty.clone()).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct EnumType {
+    pub name: String,
+    pub variants: Vec<(String, VariantFields)>,
+}
+
+/// Structural type of anonymous object.
+///
+/// Fields are sorted by name, so objects and structs with the same fields have the same layout
+/// whatever order the fields are written in.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ObjectType {
+    pub fields: Vec<(String, Type)>,
+}
+
+impl fmt::Display for ObjectType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#{{ {} }}",
+            self.fields
+                .iter()
+                .map(|(name, ty)| format!("{name}: {ty}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+/// Checks that values with `found` fields can be stored where ones with `expected` fields are
+/// expected, both are sorted by name.
+fn fields_match(expected: &[(String, Type)], found: &[(String, Type)]) -> bool {
+    expected.len() == found.len()
+        && expected
+            .iter()
+            .zip(found)
+            .all(|((name, ty), (found_name, found_ty))| name == found_name && ty.compare(found_ty))
+}
+
 pub trait AsType {
     fn as_type() -> Type;
 }
@@ -56,6 +129,12 @@ impl AsType for i64 {
     }
 }
 
+impl AsType for f64 {
+    fn as_type() -> Type {
+        Type::Float
+    }
+}
+
 impl AsType for f32 {
     fn as_type() -> Type {
         Type::Float
@@ -74,13 +153,62 @@ impl AsType for () {
     }
 }
 
+impl<T: AsType> AsType for Option<T> {
+    fn as_type() -> Type {
+        Type::Optional(Box::new(T::as_type()))
+    }
+}
+
 impl Type {
+    /// Checks that value of type `other` can be used where value of this type is expected.
+    ///
+    /// Values are wrapped into optionals implicitly, but optionals are never unwrapped.
     #[must_use]
     pub fn compare(&self, other: &Self) -> bool {
-        if matches!(self, Self::Any) || matches!(other, Self::Any) {
-            true
-        } else {
-            self == other
+        match (self, other) {
+            (Self::Any, _) | (_, Self::Any) | (Self::Optional(_), Self::None) => true,
+            (Self::Optional(value), Self::Optional(other))
+            | (Self::Array(value), Self::Array(other)) => value.compare(other),
+            (Self::Optional(value), other) => value.compare(other),
+            (
+                Self::Object(ObjectType { fields }) | Self::Struct(StructType { fields, .. }),
+                Self::Object(ObjectType { fields: found }),
+            )
+            | (
+                Self::Object(ObjectType { fields }),
+                Self::Struct(StructType { fields: found, .. }),
+            ) => fields_match(fields, found),
+            _ => self == other,
+        }
+    }
+
+    /// Checks whether part of the type is unknown, like element type of empty array `[]`.
+    #[must_use]
+    pub fn has_unknown(&self) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Optional(value) | Self::Array(value) => value.has_unknown(),
+            Self::Object(ObjectType { fields }) => fields.iter().any(|(_, ty)| ty.has_unknown()),
+            _ => false,
+        }
+    }
+
+    /// Returns type of the values produced by iterating over value of this type.
+    #[must_use]
+    pub fn iterator_item(&self) -> Option<Self> {
+        match self {
+            Self::Any => Some(Self::Any),
+            Self::Range => Some(Self::Integer),
+            Self::String => Some(Self::String),
+            Self::Array(value) => Some((**value).clone()),
+            Self::Function(FunctionType { args, output_type }) if args.is_empty() => {
+                if let Self::Optional(value) = &**output_type {
+                    Some((**value).clone())
+                } else {
+                    None
+                }
+            }
+            _ => None,
         }
     }
 }
@@ -21,17 +21,17 @@ impl Chunk {
     }
 
     #[must_use]
-    pub fn constants(&self) -> usize {
+    pub const fn constants(&self) -> usize {
         self.constants.len()
     }
 
     #[must_use]
-    pub fn len(&self) -> usize {
+    pub const fn len(&self) -> usize {
         self.code.len()
     }
 
     #[must_use]
-    pub fn is_empty(&self) -> bool {
+    pub const fn is_empty(&self) -> bool {
         self.code.is_empty()
     }
 
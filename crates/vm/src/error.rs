@@ -0,0 +1,141 @@
+use std::{convert::Infallible, error::Error, fmt};
+
+use tapt_typing::Type;
+
+use crate::Value;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VmErrorKind {
+    IndexOutOfBounds {
+        index: i64,
+        len: usize,
+    },
+    /// Result of integer operation doesn't fit into `int`.
+    IntegerOverflow,
+    DivisionByZero,
+    /// Operator can't be applied to values of these types.
+    InvalidOperands {
+        operator: String,
+    },
+    NotIterable(String),
+    NotCallable(String),
+    /// Instruction pointer left the chunk.
+    InvalidInstruction(usize),
+    /// Instruction needs more values than there are on the stack.
+    StackUnderflow,
+    /// Value has another type than instruction or native function expects.
+    TypeMismatch {
+        expected: Type,
+        found: String,
+    },
+    /// Native function was called with fewer arguments than it takes.
+    MissingArgument,
+    /// Value can't be converted by `as` cast.
+    InvalidCastOperand(String),
+    /// Global variable is read before the statement declaring it is executed.
+    UninitializedGlobal,
+    /// Instruction refers to variable slot which was never assigned.
+    InvalidVariable,
+    /// Instruction got value it can't work with, `expected` describes values it accepts.
+    UnexpectedValue {
+        expected: &'static str,
+        found: String,
+    },
+    /// Functions are nested deeper than `MAX_CALL_DEPTH` calls.
+    StackOverflow,
+}
+
+impl VmErrorKind {
+    #[must_use]
+    pub fn type_mismatch(expected: Type, found: &Value) -> Self {
+        Self::TypeMismatch {
+            expected,
+            found: found.to_string(),
+        }
+    }
+
+    #[must_use]
+    pub fn unexpected_value(expected: &'static str, found: &Value) -> Self {
+        Self::UnexpectedValue {
+            expected,
+            found: found.to_string(),
+        }
+    }
+}
+
+impl From<Infallible> for VmErrorKind {
+    fn from(value: Infallible) -> Self {
+        match value {}
+    }
+}
+
+/// Function call which was active when the error occurred.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceFrame {
+    /// Name of the called function.
+    pub function: String,
+    /// Source line the function was called from.
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VmError {
+    pub kind: VmErrorKind,
+    /// Source line of the instruction that failed.
+    pub line: usize,
+    /// Calls leading to the error, innermost first.
+    pub trace: Vec<TraceFrame>,
+}
+
+impl VmError {
+    #[must_use]
+    pub const fn new(kind: VmErrorKind, line: usize) -> Self {
+        Self {
+            kind,
+            line,
+            trace: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for VmErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IndexOutOfBounds { index, len } => {
+                write!(f, "index {index} is out of bounds for length {len}")
+            }
+            Self::IntegerOverflow => f.write_str("integer overflow"),
+            Self::DivisionByZero => f.write_str("division by zero"),
+            Self::InvalidOperands { operator } => write!(f, "invalid operands for `{operator}`"),
+            Self::NotIterable(value) => write!(f, "`{value}` is not iterable"),
+            Self::NotCallable(value) => write!(f, "`{value}` is not callable"),
+            Self::InvalidInstruction(position) => {
+                write!(f, "no instruction at position {position}")
+            }
+            Self::StackUnderflow => f.write_str("stack underflow"),
+            Self::TypeMismatch { expected, found } => {
+                write!(f, "expected value of type `{expected}`, found `{found}`")
+            }
+            Self::MissingArgument => f.write_str("missing argument of native function"),
+            Self::InvalidCastOperand(value) => write!(f, "`{value}` can't be cast"),
+            Self::UninitializedGlobal => {
+                f.write_str("global variable is used before its declaration")
+            }
+            Self::InvalidVariable => f.write_str("access to variable which doesn't exist"),
+            Self::UnexpectedValue { expected, found } => {
+                write!(f, "expected {expected}, found `{found}`")
+            }
+            Self::StackOverflow => f.write_str("stack overflow"),
+        }
+    }
+}
+
+impl fmt::Display for VmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.kind.fmt(f)
+    }
+}
+
+impl Error for VmError {}
+
+pub type VmResult<T> = Result<T, VmError>;
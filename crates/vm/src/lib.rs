@@ -1,27 +1,42 @@
 mod chunk;
+mod error;
+mod method;
 mod op;
 mod value;
 
-pub use self::{chunk::Chunk, op::OpCode, value::*};
-use std::any::Any;
+pub use self::{
+    chunk::Chunk,
+    error::{TraceFrame, VmError, VmErrorKind, VmResult},
+    method::method,
+    op::{Cast, OpCode},
+    value::*,
+};
+use std::{any::Any, cell::RefCell, cmp::Ordering, rc::Rc};
 use tapt_parser::prelude::Operator;
+use tapt_typing::Type;
+
+/// Maximum number of nested function calls, each of them takes space on the native stack.
+pub const MAX_CALL_DEPTH: usize = 200;
 
 #[derive(Debug, Default)]
 pub struct StackFrame {
     position: usize,
     stack_position: usize,
     returned: Option<Value>,
-    slots: Vec<Value>,
+    slots: Vec<Slot>,
+    /// Variables captured by the closure this frame belongs to.
+    upvalues: Rc<[Slot]>,
 }
 
 impl StackFrame {
     #[must_use]
-    pub const fn new(stack_position: usize) -> Self {
+    pub const fn new(stack_position: usize, upvalues: Rc<[Slot]>) -> Self {
         Self {
             position: 0,
             stack_position,
             slots: Vec::new(),
             returned: None,
+            upvalues,
         }
     }
 
@@ -47,17 +62,28 @@ impl StackFrame {
     //     }
     // }
 
-    #[must_use]
-    pub fn get_slot(&self, slot: usize) -> Value {
-        self.slots[slot].clone()
+    /// # Errors
+    ///
+    /// Returns `VmErrorKind` if nothing was stored in the slot
+    pub fn get_slot(&self, slot: usize) -> Result<Value, VmErrorKind> {
+        self.slots
+            .get(slot)
+            .map(|value| value.borrow().clone())
+            .ok_or(VmErrorKind::InvalidVariable)
     }
 
     pub fn set_slot(&mut self, slot: usize, value: Value) {
-        if self.slots.is_empty() || self.slots.len() < slot + 1 {
-            self.slots.resize(slot + 1, Value::None);
+        *self.slot(slot).borrow_mut() = value;
+    }
+
+    /// Returns cell of the slot, so the variable can be shared with closures.
+    pub fn slot(&mut self, slot: usize) -> Slot {
+        if self.slots.len() < slot + 1 {
+            self.slots
+                .resize_with(slot + 1, || Rc::new(RefCell::new(Value::None)));
         }
 
-        self.slots[slot] = value;
+        self.slots[slot].clone()
     }
 
     pub fn reset(&mut self) {
@@ -73,6 +99,11 @@ pub struct VM {
     pub position: usize,
     pub stack: Vec<Value>,
     pub frames: Vec<StackFrame>,
+    /// Variables declared at the top level of the script, visible to every function. Slots of
+    /// variables whose declaration hasn't been executed yet are `None`.
+    pub globals: Vec<Option<Value>>,
+    /// Number of script functions being called.
+    depth: usize,
 }
 
 impl VM {
@@ -83,7 +114,9 @@ impl VM {
             position: 0,
             is_running: false,
             stack: Vec::new(),
-            frames: vec![StackFrame::new(0)],
+            frames: vec![StackFrame::default()],
+            globals: Vec::new(),
+            depth: 0,
         }
     }
 
@@ -100,21 +133,73 @@ impl VM {
         &mut self.frames[frame]
     }
 
+    /// Returns frame with `offset` relative to the current one.
+    fn frame_at(&mut self, offset: isize) -> Result<&mut StackFrame, VmErrorKind> {
+        (self.frames.len() - 1)
+            .checked_add_signed(offset)
+            .and_then(|frame| self.frames.get_mut(frame))
+            .ok_or(VmErrorKind::InvalidVariable)
+    }
+
+    /// Returns variable captured by the function being executed.
+    fn upvalue(&self, index: usize) -> Result<Slot, VmErrorKind> {
+        self.frame()
+            .upvalues
+            .get(index)
+            .cloned()
+            .ok_or(VmErrorKind::InvalidVariable)
+    }
+
+    /// Returns value of global variable, `None` if it is not assigned yet.
+    #[must_use]
+    pub fn get_global(&self, slot: usize) -> Option<Value> {
+        self.globals.get(slot).cloned().flatten()
+    }
+
+    pub fn set_global(&mut self, slot: usize, value: Value) {
+        if self.globals.len() < slot + 1 {
+            self.globals.resize(slot + 1, None);
+        }
+
+        self.globals[slot] = Some(value);
+    }
+
     pub fn push(&mut self, value: Value) {
         self.stack.push(value);
     }
 
-    pub fn pop(&mut self) -> Value {
-        let Some(value) = self.stack.pop() else {
-            unreachable!()
-        };
+    /// # Errors
+    ///
+    /// Returns `VmErrorKind` if the stack is empty
+    pub fn pop(&mut self) -> Result<Value, VmErrorKind> {
+        self.stack.pop().ok_or(VmErrorKind::StackUnderflow)
+    }
 
-        value
+    /// Pops value and converts it into `T`.
+    fn pop_as<T: TryFrom<Value, Error = VmErrorKind>>(&mut self) -> Result<T, VmErrorKind> {
+        T::try_from(self.pop()?)
     }
 
-    #[must_use]
-    pub fn peek(&self, distance: usize) -> Value {
-        self.stack[self.stack.len() - 1 - distance].clone()
+    /// Pops `count` values, returning them in the order they were pushed.
+    fn pop_many(&mut self, count: usize) -> Result<Vec<Value>, VmErrorKind> {
+        let start = self
+            .stack
+            .len()
+            .checked_sub(count)
+            .ok_or(VmErrorKind::StackUnderflow)?;
+
+        Ok(self.stack.split_off(start))
+    }
+
+    /// # Errors
+    ///
+    /// Returns `VmErrorKind` if there are not enough values on the stack
+    pub fn peek(&self, distance: usize) -> Result<Value, VmErrorKind> {
+        self.stack
+            .len()
+            .checked_sub(distance + 1)
+            .map(|index| self.stack[index].clone())
+            .ok_or(VmErrorKind::StackUnderflow)
     }
 
     pub fn reset(&mut self) {
@@ -124,7 +209,11 @@ impl VM {
     }
 
     pub fn push_frame(&mut self) {
-        self.frames.push(StackFrame::new(self.stack.len()));
+        // blocks see the same captured variables as the function they are in
+        let upvalues = self.frame().upvalues.clone();
+
+        self.frames
+            .push(StackFrame::new(self.stack.len(), upvalues));
     }
 
     pub fn pop_frame(&mut self) -> Option<Value> {
@@ -137,25 +226,52 @@ impl VM {
         frame.returned
     }
 
-    fn binary_op(&mut self, operator: &Operator) {
-        let b = self.pop();
-        let a = self.pop();
+    /// Applies `operator` to integers, failing if the result doesn't fit into `int`.
+    fn integer_op(operator: &Operator, a: i64, b: i64) -> Result<i64, VmErrorKind> {
+        let shift = || u32::try_from(b).ok();
+
+        if matches!(operator, Operator::Div | Operator::Rem) && b == 0 {
+            return Err(VmErrorKind::DivisionByZero);
+        }
+
+        match operator {
+            Operator::Add => a.checked_add(b),
+            Operator::Sub => a.checked_sub(b),
+            Operator::Mul => a.checked_mul(b),
+            Operator::Div => a.checked_div(b),
+            Operator::Rem => a.checked_rem(b),
+            Operator::BitAnd => Some(a & b),
+            Operator::BitOr => Some(a | b),
+            Operator::BitXor => Some(a ^ b),
+            Operator::Shl => shift().and_then(|b| a.checked_shl(b)),
+            Operator::Shr => shift().and_then(|b| a.checked_shr(b)),
+            _ => unreachable!(),
+        }
+        .ok_or(VmErrorKind::IntegerOverflow)
+    }
+
+    fn binary_op(&mut self, operator: &Operator) -> Result<(), VmErrorKind> {
+        let b = self.pop()?;
+        let a = self.pop()?;
 
         match operator {
             Operator::Equal => self.push(Value::Boolean(a == b)),
-            Operator::GreaterThan => self.push(Value::Boolean(a > b)),
-            Operator::LessThan => self.push(Value::Boolean(a < b)),
+            Operator::GreaterThan => {
+                self.push(Value::Boolean(a.compare(&b) == Some(Ordering::Greater)));
+            }
+            Operator::LessThan => self.push(Value::Boolean(a.compare(&b) == Some(Ordering::Less))),
+            // incomparable values like NaN are neither less, greater nor equal
+            Operator::GreaterEqual => self.push(Value::Boolean(matches!(
+                a.compare(&b),
+                Some(Ordering::Greater | Ordering::Equal)
+            ))),
+            Operator::LessEqual => self.push(Value::Boolean(matches!(
+                a.compare(&b),
+                Some(Ordering::Less | Ordering::Equal)
+            ))),
             operator => match (a, b) {
                 (Value::Integer(a), Value::Integer(b)) => {
-                    let value = match operator {
-                        Operator::Add => a + b,
-                        Operator::Sub => a - b,
-                        Operator::Mul => a * b,
-                        Operator::Div => a / b,
-                        _ => unreachable!(),
-                    };
-
-                    self.push(Value::Integer(value));
+                    self.push(Value::Integer(Self::integer_op(operator, a, b)?));
                 }
                 (Value::Float(a), Value::Float(b)) => {
                     let value = match operator {
@@ -163,189 +279,632 @@ impl VM {
                         Operator::Sub => a - b,
                         Operator::Mul => a * b,
                         Operator::Div => a / b,
+                        Operator::Rem => a % b,
                         _ => unreachable!(),
                     };
 
                     self.push(Value::Float(value));
                 }
                 (a, b) => {
-                    panic!("SUKA NELZYA {a:?} {operator} {b:?}");
+                    if operator == &Operator::Add
+                        && let (Some(a), Some(b)) = (a.as_string(), b.as_string())
+                    {
+                        self.push(Value::from(a + &b));
+                    } else {
+                        return Err(VmErrorKind::InvalidOperands {
+                            operator: operator.to_string(),
+                        });
+                    }
                 }
             },
         }
+
+        Ok(())
     }
 
-    fn call(&mut self, func: &Function, args: usize) {
+    /// Runs `func` called from source `line`, recording it in the trace if it fails.
+    fn call(
+        &mut self,
+        func: &Function,
+        upvalues: Rc<[Slot]>,
+        args: usize,
+        line: usize,
+    ) -> VmResult<()> {
         let position = self.position;
+        let error = |kind| VmError::new(kind, line);
 
-        let mut args = self.stack.split_off(self.stack.len() - args);
+        if self.depth == MAX_CALL_DEPTH {
+            return Err(error(VmErrorKind::StackOverflow));
+        }
 
-        args.push(self.pop());
+        let mut args = self.pop_many(args).map_err(error)?;
 
-        let mut frame = StackFrame::new(self.stack.len());
+        args.push(self.pop().map_err(error)?);
 
-        frame.slots = args;
+        let mut frame = StackFrame::new(self.stack.len(), upvalues);
+
+        frame.slots = args
+            .into_iter()
+            .map(|value| Rc::new(RefCell::new(value)))
+            .collect();
 
         self.frames.push(frame);
+        self.depth += 1;
+
+        let returned = self.interpret(&func.chunk);
+
+        self.depth -= 1;
+
+        let returned = returned.map_err(|mut err| {
+            err.trace.push(TraceFrame {
+                function: func.meta.name.clone(),
+                line,
+            });
 
-        self.interpret(&func.chunk);
+            err
+        })?;
 
-        if let Some(returned) = self.frames.pop().and_then(|frame| frame.returned) {
+        self.pop_frame();
+
+        // optional output is pushed even if it's `none`
+        if func.meta.output != Type::None {
             self.push(returned);
         }
 
         self.is_running = true;
         self.position = position;
+
+        Ok(())
     }
 
-    /// # Panics
+    /// Calls function placed on the stack before `args` arguments.
+    fn call_value(&mut self, args: usize, line: usize) -> VmResult<()> {
+        let error = |kind| VmError::new(kind, line);
+        let value = self.peek(args).map_err(error)?;
+        let not_callable = || error(VmErrorKind::NotCallable(value.to_string()));
+
+        let Value::Object(func) = &value else {
+            return Err(not_callable());
+        };
+
+        if let Object::Function(func) = &*func.borrow() {
+            self.call(func, Rc::default(), args, line)?;
+        } else if let Object::Closure(closure) = &*func.borrow() {
+            let Value::Object(function) = &closure.function else {
+                return Err(not_callable());
+            };
+
+            let Object::Function(function) = &*function.borrow() else {
+                return Err(not_callable());
+            };
+
+            self.call(function, closure.upvalues.clone(), args, line)?;
+        } else if let Object::NativeFunction(func) = &*func.borrow() {
+            let args = self.pop_many(args).map_err(error)?;
+
+            self.pop().map_err(error)?;
+
+            let returned = (func.func)(self, Args {
+                args: args.into_iter(),
+            })
+            .map_err(error)?;
+
+            // optional output is pushed even if it's `none`
+            if func.meta.output != Type::None {
+                self.push(returned);
+            }
+        } else {
+            return Err(not_callable());
+        }
+
+        Ok(())
+    }
+
+    fn get_iter(value: &Value) -> Result<Value, VmErrorKind> {
+        let not_iterable = || VmErrorKind::NotIterable(value.to_string());
+
+        let Value::Object(object) = value else {
+            return Err(not_iterable());
+        };
+
+        let iter = match &*object.borrow() {
+            Object::Range(range) => Iter::Range(range.clone()),
+            Object::String(value) => Iter::Chars {
+                chars: value.chars().collect(),
+                position: 0,
+            },
+            Object::Array(_) => Iter::Array {
+                array: value.clone(),
+                position: 0,
+            },
+            Object::Function(_) | Object::Closure(_) | Object::NativeFunction(_) => {
+                Iter::Function(value.clone())
+            }
+            Object::Iterator(_) => return Ok(value.clone()),
+            _ => return Err(not_iterable()),
+        };
+
+        Ok(Value::object(Object::Iterator(iter)))
+    }
+
+    /// Advances iterator, returns `None` if it is exhausted.
+    fn iter_next(&mut self, iterator: &Value, line: usize) -> VmResult<Option<Value>> {
+        let error = |kind| VmError::new(kind, line);
+        let not_iterator = || error(VmErrorKind::unexpected_value("iterator", iterator));
+
+        let Value::Object(object) = iterator else {
+            return Err(not_iterator());
+        };
+
+        let func = match &mut *object.borrow_mut() {
+            Object::Iterator(Iter::Range(range)) => {
+                return Ok(range.next().map(Value::Integer));
+            }
+            Object::Iterator(Iter::Chars { chars, position }) => {
+                return Ok(chars.get(*position).map(|character| {
+                    *position += 1;
+
+                    Value::from(character.to_string())
+                }));
+            }
+            Object::Iterator(Iter::Array { array, position }) => {
+                return Self::with_array(array, |values| {
+                    Ok(values.get(*position).map(|value| {
+                        *position += 1;
+
+                        value.clone()
+                    }))
+                })
+                .map_err(error);
+            }
+            Object::Iterator(Iter::Function(func)) => Some(func.clone()),
+            _ => None,
+        };
+
+        // iterator is displayed in the error, so it mustn't be borrowed anymore
+        let Some(func) = func else {
+            return Err(not_iterator());
+        };
+
+        let height = self.stack.len();
+
+        self.push(func);
+        self.call_value(0, line)?;
+
+        Ok(if self.stack.len() > height {
+            Some(self.pop().map_err(error)?).filter(|value| value != &Value::None)
+        } else {
+            None
+        })
+    }
+
+    /// Runs `func` with elements of array stored in `value`.
+    fn with_array<T>(
+        value: &Value,
+        func: impl FnOnce(&mut Vec<Value>) -> Result<T, VmErrorKind>,
+    ) -> Result<T, VmErrorKind> {
+        if let Value::Object(object) = value
+            && let Object::Array(values) = &mut *object.borrow_mut()
+        {
+            return func(values);
+        }
+
+        Err(VmErrorKind::unexpected_value("array", value))
+    }
+
+    /// Returns property `prop` of struct or record instance, object or enum variant.
+    fn property(value: &Value, prop: usize) -> Option<Value> {
+        let Value::Object(object) = value else {
+            return None;
+        };
+
+        match &*object.borrow() {
+            Object::StructInstance(value) => value.fields.get(prop).map(|(_, value)| value.clone()),
+            Object::RecordInstance(value) => value.fields.get(prop).cloned(),
+            Object::Object(fields) => fields.get(prop).map(|(_, value)| value.clone()),
+            Object::Variant(variant) => variant.fields.get(prop).cloned(),
+            _ => None,
+        }
+    }
+
+    /// Assigns property `prop` of struct or record instance or object, returns `None` if it
+    /// doesn't exist.
+    fn set_property(value: &Value, prop: usize, property: Value) -> Option<()> {
+        let Value::Object(object) = value else {
+            return None;
+        };
+
+        match &mut *object.borrow_mut() {
+            Object::StructInstance(value) => value.fields.get_mut(prop)?.1 = property,
+            Object::RecordInstance(value) => *value.fields.get_mut(prop)? = property,
+            Object::Object(fields) => fields.get_mut(prop)?.1 = property,
+            _ => return None,
+        }
+
+        Some(())
+    }
+
+    /// Converts `index` into position in array with `len` elements.
+    fn array_index(index: i64, len: usize) -> Result<usize, VmErrorKind> {
+        usize::try_from(index)
+            .ok()
+            .filter(|index| *index < len)
+            .ok_or(VmErrorKind::IndexOutOfBounds { index, len })
+    }
+
+    /// # Errors
     ///
-    /// Panics if negating value which is not number or boolean
-    pub fn interpret(&mut self, chunk: &Chunk) -> Value {
+    /// Returns `VmError` if script failed at runtime
+    pub fn interpret(&mut self, chunk: &Chunk) -> VmResult<Value> {
         self.position = 0;
         self.is_running = true;
 
         while self.is_running {
-            let (_, instruction) = &chunk[self.position];
+            let Some((line, instruction)) = chunk.code.get(self.position) else {
+                let line = chunk.code.last().map_or(0, |(line, _)| *line);
+
+                return Err(VmError::new(
+                    VmErrorKind::InvalidInstruction(self.position),
+                    line,
+                ));
+            };
 
+            // functions are called from this small frame, so every nested call takes less of the
+            // native stack
             match instruction {
-                OpCode::LoadConst(value) => {
-                    self.push(chunk.get_const_cloned(*value));
-                }
-                OpCode::Copy => self.push(self.peek(0)),
-                OpCode::PushFrame => self.push_frame(),
-                OpCode::PopFrame => {
-                    if let Some(value) = self.pop_frame() {
+                OpCode::Call(args) => self.call_value(*args, *line)?,
+                OpCode::IterNext(offset) => {
+                    let iterator = self.peek(0).map_err(|kind| VmError::new(kind, *line))?;
+
+                    if let Some(value) = self.iter_next(&iterator, *line)? {
                         self.push(value);
+                    } else {
+                        self.position += *offset;
                     }
                 }
-                OpCode::GetLocal(frame, slot) => {
-                    let value = self.frames[(self.frames.len() as isize - 1 + *frame) as usize]
-                        .get_slot(*slot);
+                instruction => self.execute(chunk, *line, instruction)?,
+            }
+
+            self.position += 1;
+        }
+
+        Ok(self.frame_mut().returned.take().unwrap_or(Value::None))
+    }
+
+    /// Executes instruction from source `line` which doesn't call functions.
+    #[allow(clippy::too_many_lines)]
+    #[inline(never)]
+    fn execute(&mut self, chunk: &Chunk, line: usize, instruction: &OpCode) -> VmResult<()> {
+        let error = |kind| VmError::new(kind, line);
 
+        match instruction {
+            OpCode::LoadConst(value) => {
+                self.push(chunk.get_const_cloned(*value));
+            }
+            OpCode::Copy => self.push(self.peek(0).map_err(error)?),
+            OpCode::CopyPair => {
+                self.push(self.peek(1).map_err(error)?);
+                self.push(self.peek(1).map_err(error)?);
+            }
+            OpCode::PushFrame => self.push_frame(),
+            OpCode::PopFrame => {
+                if let Some(value) = self.pop_frame() {
                     self.push(value);
                 }
-                OpCode::SetLocal(frame, slot) => {
-                    let value = self.pop();
+            }
+            OpCode::GetLocal(frame, slot) => {
+                let value = self
+                    .frame_at(*frame)
+                    .and_then(|frame| frame.get_slot(*slot))
+                    .map_err(error)?;
 
-                    if let Some(frame) = frame {
-                        self.frames[*frame].set_slot(*slot, value);
-                    } else {
-                        self.frame_mut().set_slot(*slot, value);
-                    }
-                }
-                OpCode::Equal => self.binary_op(&Operator::Equal),
-                OpCode::Greater => self.binary_op(&Operator::GreaterThan),
-                OpCode::Less => self.binary_op(&Operator::LessThan),
-                OpCode::Add => self.binary_op(&Operator::Add),
-                OpCode::Sub => self.binary_op(&Operator::Sub),
-                OpCode::Mul => self.binary_op(&Operator::Mul),
-                OpCode::Div => self.binary_op(&Operator::Div),
-                OpCode::Negate => match self.pop() {
-                    Value::Integer(value) => self.push(Value::Integer(-value)),
-                    Value::Float(value) => self.push(Value::Float(-value)),
-                    Value::Boolean(value) => self.push(Value::Boolean(!value)),
-                    _ => panic!("SUKA TAK NELZYA"),
-                },
-                OpCode::Return => {
-                    if self.stack.len() > self.frame().stack_position {
-                        self.frame_mut().returned = Some(self.pop());
-                    }
-                }
-                OpCode::Halt => self.is_running = false,
-                OpCode::Jump(offset) => {
-                    let unsigned_offset = offset.unsigned_abs();
+                self.push(value);
+            }
+            OpCode::SetLocal(frame, slot) => {
+                let value = self.pop().map_err(error)?;
 
-                    if *offset >= 0 {
-                        self.position += unsigned_offset;
-                    } else {
-                        self.position -= unsigned_offset;
-                    }
-                }
-                OpCode::JumpIfFalse(offset) => {
-                    if matches!(self.peek(0), Value::Boolean(_))
-                        && self.pop() == Value::Boolean(false)
-                    {
-                        self.position += *offset;
-                    }
+                self.frame_at(*frame).map_err(error)?.set_slot(*slot, value);
+            }
+            OpCode::GetGlobal(slot) => {
+                let value = self
+                    .get_global(*slot)
+                    .ok_or_else(|| error(VmErrorKind::UninitializedGlobal))?;
+
+                self.push(value);
+            }
+            OpCode::SetGlobal(slot) => {
+                let value = self.pop().map_err(error)?;
+
+                self.set_global(*slot, value);
+            }
+            OpCode::GetUpvalue(index) => {
+                let value = self.upvalue(*index).map_err(error)?.borrow().clone();
+
+                self.push(value);
+            }
+            OpCode::SetUpvalue(index) => {
+                let value = self.pop().map_err(error)?;
+
+                *self.upvalue(*index).map_err(error)?.borrow_mut() = value;
+            }
+            OpCode::Closure(function) => {
+                let constant = chunk.get_const(*function);
+                let not_function = || error(VmErrorKind::unexpected_value("function", constant));
+
+                let Value::Object(object) = constant else {
+                    return Err(not_function());
+                };
+
+                let Object::Function(function) = &*object.borrow() else {
+                    return Err(not_function());
+                };
+
+                let upvalues = function
+                    .captures
+                    .iter()
+                    .map(|capture| match capture {
+                        Capture::Local(frame, slot) => {
+                            self.frame_at(*frame).map(|frame| frame.slot(*slot))
+                        }
+                        Capture::Upvalue(index) => self.upvalue(*index),
+                    })
+                    .collect::<Result<_, _>>()
+                    .map_err(error)?;
+
+                self.push(Value::object(Object::Closure(Closure {
+                    function: Value::Object(object.clone()),
+                    upvalues,
+                })));
+            }
+            OpCode::Equal => self.binary_op(&Operator::Equal).map_err(error)?,
+            OpCode::Greater => self.binary_op(&Operator::GreaterThan).map_err(error)?,
+            OpCode::Less => self.binary_op(&Operator::LessThan).map_err(error)?,
+            OpCode::GreaterEqual => self.binary_op(&Operator::GreaterEqual).map_err(error)?,
+            OpCode::LessEqual => self.binary_op(&Operator::LessEqual).map_err(error)?,
+            OpCode::Add => self.binary_op(&Operator::Add).map_err(error)?,
+            OpCode::Sub => self.binary_op(&Operator::Sub).map_err(error)?,
+            OpCode::Mul => self.binary_op(&Operator::Mul).map_err(error)?,
+            OpCode::Div => self.binary_op(&Operator::Div).map_err(error)?,
+            OpCode::Rem => self.binary_op(&Operator::Rem).map_err(error)?,
+            OpCode::BitAnd => self.binary_op(&Operator::BitAnd).map_err(error)?,
+            OpCode::BitOr => self.binary_op(&Operator::BitOr).map_err(error)?,
+            OpCode::BitXor => self.binary_op(&Operator::BitXor).map_err(error)?,
+            OpCode::Shl => self.binary_op(&Operator::Shl).map_err(error)?,
+            OpCode::Shr => self.binary_op(&Operator::Shr).map_err(error)?,
+            OpCode::Negate => match self.pop().map_err(error)? {
+                Value::Integer(value) => {
+                    let value = value
+                        .checked_neg()
+                        .ok_or_else(|| error(VmErrorKind::IntegerOverflow))?;
+
+                    self.push(Value::Integer(value));
                 }
-                OpCode::Pop => {
-                    self.pop();
+                Value::Float(value) => self.push(Value::Float(-value)),
+                _ => {
+                    return Err(error(VmErrorKind::InvalidOperands {
+                        operator: "-".into(),
+                    }));
                 }
-                OpCode::Call(args) => {
-                    if let Value::Object(func) = self.peek(*args) {
-                        if let Object::Function(func) = &*func.borrow() {
-                            self.call(func, *args);
-                        } else if let Object::NativeFunction(func) = &*func.borrow() {
-                            let args = self.stack.split_off(self.stack.len() - *args);
-
-                            self.pop();
-
-                            let returned = (func.func)(self, Args {
-                                args: args.into_iter(),
-                            });
-
-                            if returned != Value::None {
-                                self.push(returned);
-                            }
-                        }
-                    }
+            },
+            OpCode::Cast(cast) => {
+                let value = self.pop().map_err(error)?;
+
+                self.push(cast.apply(value).map_err(error)?);
+            }
+            OpCode::Not => {
+                let value = self.pop_as::<bool>().map_err(error)?;
+
+                self.push(Value::Boolean(!value));
+            }
+            OpCode::Return => {
+                if self.stack.len() > self.frame().stack_position {
+                    self.frame_mut().returned = Some(self.pop().map_err(error)?);
                 }
-                OpCode::CreateInstance => {
-                    let Value::Object(value) = self.pop() else {
-                        unreachable!()
-                    };
+            }
+            OpCode::Exit(frames) => {
+                let value = self.pop().map_err(error)?;
 
-                    if let Object::Struct(value) = &*value.borrow() {
-                        let values = self.stack.split_off(self.stack.len() - value.fields.len());
-
-                        self.push(Value::object(Object::StructInstance(StructInstance {
-                            name: value.name.clone(),
-                            fields: values
-                                .into_iter()
-                                .zip(&value.fields)
-                                .map(|(value, (name, _))| (name.clone(), value))
-                                .collect(),
-                        })));
-                    } else if let Object::Record(value) = &*value.borrow() {
-                        let values = self.stack.split_off(self.stack.len() - value.fields.len());
-
-                        self.push(Value::object(Object::RecordInstance(RecordInstance {
-                            name: value.name.clone(),
-                            fields: values,
-                        })));
-                    } else {
-                        unreachable!()
-                    };
+                for _ in 0..*frames {
+                    self.pop_frame();
                 }
-                OpCode::GetProperty(prop) => {
-                    let value = self.pop();
-
-                    if let Value::Object(object) = value {
-                        if let Object::StructInstance(value) = &*object.borrow() {
-                            self.push(value.fields[*prop].1.clone());
-                        } else if let Object::RecordInstance(value) = &*object.borrow() {
-                            self.push(value.fields[*prop].clone());
-                        }
-                    }
+
+                self.frame_mut().returned = Some(value);
+                self.is_running = false;
+            }
+            OpCode::Halt => self.is_running = false,
+            OpCode::Jump(offset) => {
+                let unsigned_offset = offset.unsigned_abs();
+
+                if *offset >= 0 {
+                    self.position += unsigned_offset;
+                } else {
+                    self.position -= unsigned_offset;
                 }
-                OpCode::SetProperty(prop) => {
-                    let property_value = self.pop();
-                    let value = self.pop();
-
-                    if let Value::Object(object) = value {
-                        if let Object::StructInstance(value) = &mut *object.borrow_mut() {
-                            value.fields[*prop].1 = property_value;
-                        } else if let Object::RecordInstance(value) = &mut *object.borrow_mut() {
-                            value.fields[*prop] = property_value;
-                        }
+            }
+            OpCode::JumpIfFalse(offset) => {
+                if let Value::Boolean(value) = self.peek(0).map_err(error)? {
+                    self.pop().map_err(error)?;
+
+                    if !value {
+                        self.position += *offset;
                     }
                 }
             }
+            OpCode::Pop => {
+                self.pop().map_err(error)?;
+            }
+            OpCode::GetIter => {
+                let value = self.pop().map_err(error)?;
 
-            self.position += 1;
+                self.push(Self::get_iter(&value).map_err(error)?);
+            }
+            OpCode::CreateRange(inclusive) => {
+                let end = self.pop_as().map_err(error)?;
+                let start = self.pop_as().map_err(error)?;
+
+                self.push(Value::object(Object::Range(Range {
+                    start,
+                    end,
+                    inclusive: *inclusive,
+                })));
+            }
+            OpCode::CreateArray(len) => {
+                let values = self.pop_many(*len).map_err(error)?;
+
+                self.push(Value::object(Object::Array(values)));
+            }
+            OpCode::Format(len) => {
+                let value = self
+                    .pop_many(*len)
+                    .map_err(error)?
+                    .into_iter()
+                    .map(|value| value.as_string().unwrap_or_else(|| value.to_string()))
+                    .collect::<String>();
+
+                self.push(Value::from(value));
+            }
+            OpCode::CreateObject(template) => {
+                let constant = chunk.get_const(*template);
+                let not_object = || error(VmErrorKind::unexpected_value("object", constant));
+
+                let Value::Object(template) = constant else {
+                    return Err(not_object());
+                };
+
+                let Object::Object(fields) = &*template.borrow() else {
+                    return Err(not_object());
+                };
+
+                let values = self.pop_many(fields.len()).map_err(error)?;
+
+                self.push(Value::object(Object::Object(
+                    fields
+                        .iter()
+                        .zip(values)
+                        .map(|((name, _), value)| (name.clone(), value))
+                        .collect(),
+                )));
+            }
+            OpCode::CreateVariant(template) => {
+                let constant = chunk.get_const(*template);
+                let not_variant = || error(VmErrorKind::unexpected_value("enum variant", constant));
+
+                let Value::Object(template) = constant else {
+                    return Err(not_variant());
+                };
+
+                let Object::Variant(variant) = &*template.borrow() else {
+                    return Err(not_variant());
+                };
+
+                let fields = self.pop_many(variant.fields.len()).map_err(error)?;
+
+                self.push(Value::object(Object::Variant(Variant {
+                    fields,
+                    ..variant.clone()
+                })));
+            }
+            OpCode::IsVariant(tag) => {
+                let value = self.pop().map_err(error)?;
+                let not_variant = || error(VmErrorKind::unexpected_value("enum variant", &value));
+
+                let Value::Object(object) = &value else {
+                    return Err(not_variant());
+                };
+
+                let Object::Variant(variant) = &*object.borrow() else {
+                    return Err(not_variant());
+                };
+
+                self.push(Value::Boolean(variant.tag == *tag));
+            }
+            OpCode::GetIndex => {
+                let index = self.pop_as().map_err(error)?;
+                let array = self.pop().map_err(error)?;
+
+                let value = Self::with_array(&array, |values| {
+                    Self::array_index(index, values.len()).map(|index| values[index].clone())
+                })
+                .map_err(error)?;
+
+                self.push(value);
+            }
+            OpCode::SetIndex => {
+                let value = self.pop().map_err(error)?;
+                let index = self.pop_as().map_err(error)?;
+                let array = self.pop().map_err(error)?;
+
+                Self::with_array(&array, |values| {
+                    Self::array_index(index, values.len()).map(|index| values[index] = value)
+                })
+                .map_err(error)?;
+            }
+            OpCode::Contains => {
+                let range = self.pop_as::<Range>().map_err(error)?;
+                let value = self.pop_as().map_err(error)?;
+
+                self.push(Value::Boolean(range.contains(value)));
+            }
+            OpCode::CreateInstance => {
+                let instance = self.pop().map_err(error)?;
+                let not_type =
+                    || error(VmErrorKind::unexpected_value("struct or record", &instance));
+
+                let Value::Object(value) = &instance else {
+                    return Err(not_type());
+                };
+
+                if let Object::Struct(value) = &*value.borrow() {
+                    let values = self.pop_many(value.fields.len()).map_err(error)?;
+
+                    self.push(Value::object(Object::StructInstance(StructInstance {
+                        name: value.name.clone(),
+                        fields: values
+                            .into_iter()
+                            .zip(&value.fields)
+                            .map(|(value, (name, _))| (name.clone(), value))
+                            .collect(),
+                    })));
+                } else if let Object::Record(value) = &*value.borrow() {
+                    let values = self.pop_many(value.fields.len()).map_err(error)?;
+
+                    self.push(Value::object(Object::RecordInstance(RecordInstance {
+                        name: value.name.clone(),
+                        fields: values,
+                    })));
+                } else {
+                    return Err(not_type());
+                }
+            }
+            OpCode::GetProperty(prop) => {
+                let value = self.pop().map_err(error)?;
+                let no_property = || {
+                    error(VmErrorKind::unexpected_value(
+                        "value with this property",
+                        &value,
+                    ))
+                };
+
+                let property = Self::property(&value, *prop).ok_or_else(no_property)?;
+
+                self.push(property);
+            }
+            OpCode::SetProperty(prop) => {
+                let property = self.pop().map_err(error)?;
+                let value = self.pop().map_err(error)?;
+                let no_property = || {
+                    error(VmErrorKind::unexpected_value(
+                        "value with this property",
+                        &value,
+                    ))
+                };
+
+                Self::set_property(&value, *prop, property).ok_or_else(no_property)?;
+            }
+            // calls are made by `interpret`
+            OpCode::Call(_) | OpCode::IterNext(_) => {}
         }
 
-        self.frame_mut().returned.take().unwrap_or(Value::None)
+        Ok(())
     }
 }
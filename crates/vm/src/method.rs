@@ -0,0 +1,186 @@
+use crate::{Args, FunctionMetadata, NativeFunction, Object, Range, VM, Value, VmErrorKind};
+use std::rc::Rc;
+use tapt_typing::Type;
+
+fn native<F: Fn(&VM, Args) -> Result<Value, VmErrorKind> + 'static>(
+    name: &str,
+    args: Vec<Type>,
+    output: Type,
+    func: F,
+) -> NativeFunction {
+    NativeFunction {
+        meta: FunctionMetadata {
+            name: name.into(),
+            args,
+            output,
+        },
+        func: Rc::new(func),
+    }
+}
+
+/// Creates array of strings.
+fn strings<T: Into<String>>(values: impl Iterator<Item = T>) -> Value {
+    Value::object(Object::Array(
+        values.map(|value| Value::from(value.into())).collect(),
+    ))
+}
+
+/// Returns chars of `value` contained in `range`, indices out of the string are clamped.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss
+)]
+fn substring(value: &str, range: &Range) -> String {
+    let len = value.chars().count() as i64;
+    let start = range.start.clamp(0, len);
+    let end = range
+        .end
+        .saturating_add(i64::from(range.inclusive))
+        .clamp(start, len);
+
+    value
+        .chars()
+        .skip(start as usize)
+        .take((end - start) as usize)
+        .collect()
+}
+
+/// Returns built-in method `name` of values of type `ty`.
+///
+/// Method is a native function which takes value it was called on as the first argument,
+/// `meta.args` doesn't include it.
+#[must_use]
+#[allow(clippy::cast_possible_wrap, clippy::too_many_lines)]
+pub fn method(ty: &Type, name: &str) -> Option<NativeFunction> {
+    match (ty, name) {
+        (Type::Range, "contains") => Some(native(
+            name,
+            vec![Type::Integer],
+            Type::Boolean,
+            |_, mut args| {
+                let range = args.get::<Range>()?;
+
+                Ok(range.contains(args.get()?).into())
+            },
+        )),
+        (Type::Range, "len") => Some(native(name, Vec::new(), Type::Integer, |_, mut args| {
+            Ok(args.get::<Range>()?.len().into())
+        })),
+        (Type::Array(_), "len") => Some(native(name, Vec::new(), Type::Integer, |_, mut args| {
+            let value = args.get::<Value>()?;
+
+            if let Value::Object(array) = &value
+                && let Object::Array(values) = &*array.borrow()
+            {
+                return Ok(Value::Integer(values.len() as i64));
+            }
+
+            Err(VmErrorKind::type_mismatch(
+                Type::Array(Box::new(Type::Any)),
+                &value,
+            ))
+        })),
+        (Type::Integer, "wrapping_add") => Some(native(
+            name,
+            vec![Type::Integer],
+            Type::Integer,
+            |_, mut args| {
+                let value = args.get::<i64>()?;
+
+                Ok(value.wrapping_add(args.get()?).into())
+            },
+        )),
+        (Type::Integer, "saturating_add") => Some(native(
+            name,
+            vec![Type::Integer],
+            Type::Integer,
+            |_, mut args| {
+                let value = args.get::<i64>()?;
+
+                Ok(value.saturating_add(args.get()?).into())
+            },
+        )),
+        (Type::String, "len") => Some(native(name, Vec::new(), Type::Integer, |_, mut args| {
+            Ok(Value::Integer(args.get::<String>()?.chars().count() as i64))
+        })),
+        (Type::String, "chars") => Some(native(
+            name,
+            Vec::new(),
+            Type::Array(Box::new(Type::String)),
+            |_, mut args| Ok(strings(args.get::<String>()?.chars())),
+        )),
+        (Type::String, "split") => Some(native(
+            name,
+            vec![Type::String],
+            Type::Array(Box::new(Type::String)),
+            |_, mut args| {
+                let value = args.get::<String>()?;
+
+                Ok(strings(value.split(&args.get::<String>()?)))
+            },
+        )),
+        (Type::String, "trim") => Some(native(name, Vec::new(), Type::String, |_, mut args| {
+            Ok(args.get::<String>()?.trim().into())
+        })),
+        (Type::String, "contains") => Some(native(
+            name,
+            vec![Type::String],
+            Type::Boolean,
+            |_, mut args| {
+                let value = args.get::<String>()?;
+
+                Ok(value.contains(&args.get::<String>()?).into())
+            },
+        )),
+        (Type::String, "starts_with") => Some(native(
+            name,
+            vec![Type::String],
+            Type::Boolean,
+            |_, mut args| {
+                let value = args.get::<String>()?;
+
+                Ok(value.starts_with(&args.get::<String>()?).into())
+            },
+        )),
+        (Type::String, "replace") => Some(native(
+            name,
+            vec![Type::String, Type::String],
+            Type::String,
+            |_, mut args| {
+                let value = args.get::<String>()?;
+                let from = args.get::<String>()?;
+
+                Ok(value.replace(&from, &args.get::<String>()?).into())
+            },
+        )),
+        (Type::String, "to_upper") => {
+            Some(native(name, Vec::new(), Type::String, |_, mut args| {
+                Ok(args.get::<String>()?.to_uppercase().into())
+            }))
+        }
+        (Type::String, "parse_int") => Some(native(
+            name,
+            Vec::new(),
+            Type::Optional(Box::new(Type::Integer)),
+            |_, mut args| Ok(args.get::<String>()?.trim().parse::<i64>().ok().into()),
+        )),
+        (Type::String, "parse_float") => Some(native(
+            name,
+            Vec::new(),
+            Type::Optional(Box::new(Type::Float)),
+            |_, mut args| Ok(args.get::<String>()?.trim().parse::<f64>().ok().into()),
+        )),
+        (Type::String, "substring") => Some(native(
+            name,
+            vec![Type::Range],
+            Type::String,
+            |_, mut args| {
+                let value = args.get::<String>()?;
+
+                Ok(substring(&value, &args.get()?).into())
+            },
+        )),
+        _ => None,
+    }
+}
@@ -1,3 +1,33 @@
+use crate::{Value, VmErrorKind};
+
+/// Conversion of value performed by `as` expression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Cast {
+    IntToFloat,
+    /// Truncates towards zero, saturating at bounds of `int`, `NaN` becomes `0`.
+    FloatToInt,
+    NumberToString,
+    BoolToInt,
+}
+
+impl Cast {
+    /// # Errors
+    ///
+    /// Returns `VmErrorKind` if cast can't convert value of such type
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+    pub fn apply(self, value: Value) -> Result<Value, VmErrorKind> {
+        Ok(match (self, value) {
+            (Self::IntToFloat, Value::Integer(value)) => Value::Float(value as f64),
+            (Self::FloatToInt, Value::Float(value)) => Value::Integer(value as i64),
+            (Self::NumberToString, value @ (Value::Integer(_) | Value::Float(_))) => {
+                Value::from(value.to_string())
+            }
+            (Self::BoolToInt, Value::Boolean(value)) => Value::Integer(i64::from(value)),
+            (_, value) => return Err(VmErrorKind::InvalidCastOperand(value.to_string())),
+        })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(u8)] // !!!! BYTE code !!!!
 pub enum OpCode {
@@ -6,23 +36,55 @@ pub enum OpCode {
     Equal,
     Greater,
     Less,
+    GreaterEqual,
+    LessEqual,
     Add,
     Sub,
     Mul,
     Div,
+    Rem,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
     Negate,
+    Not,
+    Cast(Cast),
     Return,
+    /// Returns value on top of the stack from the function, leaving frames of its blocks.
+    Exit(usize),
     SetProperty(usize),
     GetProperty(usize),
     GetLocal(isize, usize),
-    SetLocal(Option<usize>, usize),
+    SetLocal(isize, usize),
+    GetGlobal(usize),
+    SetGlobal(usize),
+    GetUpvalue(usize),
+    SetUpvalue(usize),
     Jump(isize),
     JumpIfFalse(usize),
     Call(usize),
+    /// Creates closure of function constant, capturing its variables.
+    Closure(usize),
     Copy,
+    /// Duplicates two values on top of the stack, keeping their order.
+    CopyPair,
     PushFrame,
     PopFrame,
     CreateInstance,
+    CreateRange(bool),
+    CreateArray(usize),
+    /// Joins values on top of the stack into string, strings are inserted as is.
+    Format(usize),
+    CreateObject(usize),
+    CreateVariant(usize),
+    IsVariant(usize),
+    GetIndex,
+    SetIndex,
+    Contains,
+    GetIter,
+    IterNext(usize),
     Halt, // You should halt yourself NOW!
 }
 
@@ -30,7 +92,14 @@ impl OpCode {
     #[must_use]
     pub const fn size(&self) -> usize {
         match self {
-            Self::LoadConst(_) | Self::GetLocal(..) | Self::SetLocal(..) => 2,
+            Self::LoadConst(_)
+            | Self::Closure(_)
+            | Self::GetLocal(..)
+            | Self::SetLocal(..)
+            | Self::GetGlobal(_)
+            | Self::SetGlobal(_)
+            | Self::GetUpvalue(_)
+            | Self::SetUpvalue(_) => 2,
             _ => 1,
         }
     }
@@ -2,33 +2,123 @@ use std::{cmp::Ordering, fmt, rc::Rc, cell::RefCell, vec::IntoIter};
 
 use tapt_typing::Type;
 
-use crate::{chunk::Chunk, VM};
+use crate::{VM, VmErrorKind, chunk::Chunk};
 
 pub struct Args {
     pub(crate) args: IntoIter<Value>,
 }
 
 impl Args {
-    pub fn get<T: From<Value>>(&mut self) -> T {
-        let Some(value) = self.args.next() else {
-            unreachable!()
-        };
-
-        value.into()
+    /// Takes the next argument, converting it into `T`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VmErrorKind` if there are no arguments left or the argument has another type
+    pub fn get<T: TryFrom<Value>>(&mut self) -> Result<T, VmErrorKind>
+    where
+        VmErrorKind: From<T::Error>,
+    {
+        let value = self.args.next().ok_or(VmErrorKind::MissingArgument)?;
+
+        Ok(T::try_from(value)?)
     }
 }
 
-pub type NativeFunc = Rc<dyn Fn(&VM, Args) -> Value>;
+pub type NativeFunc = Rc<dyn Fn(&VM, Args) -> Result<Value, VmErrorKind>>;
 
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub enum Object {
     String(String),
     Function(Function),
+    Closure(Closure),
     NativeFunction(NativeFunction),
     Struct(Struct),
     Record(Record),
     StructInstance(StructInstance),
     RecordInstance(RecordInstance),
+    Enum(Enum),
+    Variant(Variant),
+    Range(Range),
+    Array(Vec<Value>),
+    /// Anonymous object created by `#{ ... }` expression.
+    Object(Vec<(String, Value)>),
+    Iterator(Iter),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Range {
+    pub start: i64,
+    pub end: i64,
+    pub inclusive: bool,
+}
+
+impl Range {
+    #[must_use]
+    pub const fn contains(&self, value: i64) -> bool {
+        self.start <= value && (value < self.end || self.inclusive && value == self.end)
+    }
+
+    /// Returns count of the values in range, saturating at bounds of `int`.
+    #[must_use]
+    pub const fn len(&self) -> i64 {
+        let len = self
+            .end
+            .saturating_sub(self.start)
+            .saturating_add(if self.inclusive { 1 } else { 0 });
+
+        if len > 0 { len } else { 0 }
+    }
+
+    /// Removes the first value of the range and returns it, `None` if range is empty.
+    pub const fn next(&mut self) -> Option<i64> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let value = self.start;
+
+        if let Some(start) = self.start.checked_add(1) {
+            self.start = start;
+        } else {
+            // `int::MAX` was the last value of inclusive range
+            self.inclusive = false;
+        }
+
+        Some(value)
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        !self.contains(self.start)
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}",
+            self.start,
+            if self.inclusive { "..=" } else { ".." },
+            self.end
+        )
+    }
+}
+
+/// State of the value being iterated by `for` loop.
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum Iter {
+    Range(Range),
+    Chars {
+        chars: Vec<char>,
+        position: usize,
+    },
+    Array {
+        array: Value,
+        position: usize,
+    },
+    /// User-defined iterator, function is called until it returns `none`.
+    Function(Value),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -43,6 +133,41 @@ pub struct Record {
     pub fields: Vec<Type>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Enum {
+    pub name: String,
+    pub variants: Vec<String>,
+}
+
+/// Value of enum, `tag` is the index of variant in enum declaration.
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct Variant {
+    pub enum_name: String,
+    pub name: String,
+    pub tag: usize,
+    pub fields: Vec<Value>,
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.enum_name, self.name)?;
+
+        if self.fields.is_empty() {
+            Ok(())
+        } else {
+            write!(
+                f,
+                "({})",
+                self.fields
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct RecordInstance {
     pub name: String,
@@ -104,17 +229,37 @@ pub struct FunctionMetadata {
     pub output: Type,
 }
 
+/// Where closure takes the captured variable from when it is created.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Capture {
+    /// Slot of the frame with given offset relative to the current one.
+    Local(isize, usize),
+    /// Variable captured by the function creating the closure.
+    Upvalue(usize),
+}
+
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct Function {
     pub meta: FunctionMetadata,
     pub chunk: Chunk,
+    pub captures: Vec<Capture>,
+}
+
+/// Variable which can be shared between frame and closures capturing it.
+pub type Slot = Rc<RefCell<Value>>;
+
+/// Function together with variables it captured, `function` holds `Object::Function`.
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct Closure {
+    pub function: Value,
+    pub upvalues: Rc<[Slot]>,
 }
 
 #[derive(Debug, Clone)]
 pub enum Value {
     None,
     Integer(i64),
-    Float(f32),
+    Float(f64),
     Boolean(bool),
     Object(Rc<RefCell<Object>>),
 }
@@ -144,6 +289,7 @@ impl PartialOrd for Value {
 }
 
 impl fmt::Display for Value {
+    #[allow(clippy::too_many_lines)]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::None => f.write_str("()"),
@@ -164,6 +310,7 @@ impl fmt::Display for Value {
                         .join(", "),
                     func.meta.output
                 ),
+                Object::Closure(closure) => closure.function.fmt(f),
                 Object::NativeFunction(func) => write!(
                     f,
                     "func[native] {}({}): {}",
@@ -209,6 +356,30 @@ impl fmt::Display for Value {
                         .collect::<Vec<_>>()
                         .join(",\n")
                 ),
+                Object::Enum(value) => {
+                    write!(f, "enum {} {{ {} }}", value.name, value.variants.join(", "))
+                }
+                Object::Variant(variant) => variant.fmt(f),
+                Object::Range(range) => range.fmt(f),
+                Object::Array(values) => write!(
+                    f,
+                    "[{}]",
+                    values
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                Object::Object(fields) => write!(
+                    f,
+                    "#{{ {} }}",
+                    fields
+                        .iter()
+                        .map(|(name, value)| format!("{name}: {value}"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                Object::Iterator(_) => f.write_str("iterator"),
                 Object::StructInstance(instance) => write!(
                     f,
                     "struct[instance] {} {{\n{}\n}}",
@@ -239,6 +410,20 @@ impl Value {
             None
         }
     }
+
+    /// Compares numbers of the same type or strings lexicographically, other values aren't
+    /// ordered.
+    #[must_use]
+    pub fn compare(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Self::Integer(a), Self::Integer(b)) => Some(a.cmp(b)),
+            (Self::Float(a), Self::Float(b)) => a.partial_cmp(b),
+            (a, b) => match (a.as_string(), b.as_string()) {
+                (Some(a), Some(b)) => Some(a.cmp(&b)),
+                _ => None,
+            },
+        }
+    }
 }
 
 impl From<String> for Value {
@@ -253,9 +438,15 @@ impl From<&str> for Value {
     }
 }
 
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
 impl From<f32> for Value {
     fn from(value: f32) -> Self {
-        Self::Float(value)
+        Self::Float(value.into())
     }
 }
 
@@ -277,46 +468,74 @@ impl From<()> for Value {
     }
 }
 
-impl From<Value> for bool {
-    fn from(value: Value) -> Self {
-        let Value::Boolean(value) = value else {
-            unreachable!()
-        };
+impl<T: Into<Self>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::None, Into::into)
+    }
+}
+
+impl TryFrom<Value> for bool {
+    type Error = VmErrorKind;
 
-        value
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Boolean(value) => Ok(value),
+            value => Err(VmErrorKind::type_mismatch(Type::Boolean, &value)),
+        }
     }
 }
 
-impl From<Value> for i64 {
-    fn from(value: Value) -> Self {
-        let Value::Integer(value) = value else {
-            unreachable!()
-        };
+impl TryFrom<Value> for i64 {
+    type Error = VmErrorKind;
 
-        value
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Integer(value) => Ok(value),
+            value => Err(VmErrorKind::type_mismatch(Type::Integer, &value)),
+        }
     }
 }
 
-impl From<Value> for f32 {
-    fn from(value: Value) -> Self {
-        let Value::Float(value) = value else {
-            unreachable!()
-        };
+impl TryFrom<Value> for f64 {
+    type Error = VmErrorKind;
 
-        value
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Float(value) => Ok(value),
+            value => Err(VmErrorKind::type_mismatch(Type::Float, &value)),
+        }
+    }
+}
+
+impl TryFrom<Value> for f32 {
+    type Error = VmErrorKind;
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        f64::try_from(value).map(|value| value as Self)
     }
 }
 
-impl From<Value> for String {
-    fn from(value: Value) -> Self {
-        let Value::Object(value) = value else {
-            unreachable!()
-        };
+impl TryFrom<Value> for Range {
+    type Error = VmErrorKind;
 
-        let Object::String(value) = &*value.borrow() else {
-            unreachable!()
-        };
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        if let Value::Object(object) = &value
+            && let Object::Range(range) = &*object.borrow()
+        {
+            return Ok(range.clone());
+        }
 
-        value.clone()
+        Err(VmErrorKind::type_mismatch(Type::Range, &value))
+    }
+}
+
+impl TryFrom<Value> for String {
+    type Error = VmErrorKind;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        value
+            .as_string()
+            .ok_or_else(|| VmErrorKind::type_mismatch(Type::String, &value))
     }
 }